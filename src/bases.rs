@@ -0,0 +1,54 @@
+use yew::format::Json;
+use yew::services::storage::{Area, StorageService};
+
+const BASES_KEY: &str = "prun.bases";
+
+pub enum BaseAction {
+    Add(String),
+    Remove(String),
+}
+
+// The planets, by nat_id, where the company has a base. Kept in
+// localStorage like the presets and favourites.
+pub struct BaseStore {
+    storage: Option<StorageService>,
+    bases: Vec<String>,
+}
+
+impl BaseStore {
+    pub fn new() -> Self {
+        let storage = StorageService::new(Area::Local).ok();
+        let bases = match &storage {
+            Some(s) => {
+                let Json(bases) = s.restore(BASES_KEY);
+                bases.unwrap_or_default()
+            }
+            None => Vec::new(),
+        };
+        Self { storage, bases }
+    }
+    pub fn all(&self) -> &Vec<String> {
+        &self.bases
+    }
+    pub fn add(&mut self, nat_id: String) {
+        if !self.bases.contains(&nat_id) {
+            self.bases.push(nat_id);
+            self.store();
+        }
+    }
+    pub fn remove(&mut self, nat_id: &str) {
+        self.bases.retain(|b| b != nat_id);
+        self.store();
+    }
+    fn store(&mut self) {
+        if let Some(storage) = &mut self.storage {
+            storage.store(BASES_KEY, Json(&self.bases));
+        }
+    }
+}
+
+impl Default for BaseStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
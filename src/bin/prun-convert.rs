@@ -0,0 +1,57 @@
+// Regenerates json/stars.json, planets.json, resources.json and the
+// materials.json catalogue from raw FIO dumps saved locally (see fio.rs
+// for the files expected).
+//
+//     prun-convert DUMP_DIR [OUT_DIR]
+//
+// The written files are read back through the app's own models and must
+// give the same records and the same bytes, or the command fails.
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+use prun_rusty_little_helper::dataset::{read_json, Dataset, MATERIALS_FILE};
+use prun_rusty_little_helper::fio::{catalogue, convert, MATERIALS_DUMP, PLANETS_DUMP, SYSTEMS_DUMP};
+use prun_rusty_little_helper::validate::validate;
+
+fn run(dumps: PathBuf, out: PathBuf) -> anyhow::Result<()> {
+    let systems = read_json::<Vec<_>>(&dumps.join(SYSTEMS_DUMP))?;
+    let planets = read_json::<Vec<_>>(&dumps.join(PLANETS_DUMP))?;
+    let materials = read_json::<Vec<_>>(&dumps.join(MATERIALS_DUMP))?;
+    let data = convert(&systems, &planets, &materials)?;
+    let catalogue = catalogue(&materials);
+    for issue in validate(&data.stars, &data.planets, &data.resources) {
+        eprintln!("{}", issue);
+    }
+
+    data.write_dir(&out)?;
+    let read_back = Dataset::read_dir(&out)?;
+    if read_back != data {
+        anyhow::bail!("{} doesn't read back as the records written", out.display());
+    }
+    if read_back.to_files()? != data.to_files()? {
+        anyhow::bail!("{} doesn't write back byte for byte", out.display());
+    }
+    fs::write(out.join(MATERIALS_FILE), serde_json::to_string(&catalogue)?)?;
+    println!(
+        "{} stars, {} planets, {} resources, {} materials written to {}",
+        data.stars.len(), data.planets.len(), data.resources.len(), catalogue.len(), out.display()
+    );
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (dumps, out) = match &args[..] {
+        [dumps] => (PathBuf::from(dumps), PathBuf::from("json")),
+        [dumps, out] => (PathBuf::from(dumps), PathBuf::from(out)),
+        _ => {
+            eprintln!("usage: prun-convert DUMP_DIR [OUT_DIR]");
+            process::exit(2);
+        }
+    };
+    if let Err(e) = run(dumps, out) {
+        eprintln!("error: {:#}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,61 @@
+// Shows what changed between two snapshots of the data, eg. the json/ in
+// use and a fresh prun-convert of new dumps.
+//
+//     prun-diff OLD NEW [--json]
+//
+// OLD and NEW are each a directory of JSON files or a packed universe.bin.
+use std::fs;
+use std::path::Path;
+use std::process;
+
+use prun_rusty_little_helper::dataset::Dataset;
+use prun_rusty_little_helper::diff::diff;
+use prun_rusty_little_helper::pack::unpack;
+
+fn read(path: &Path) -> anyhow::Result<Dataset> {
+    if path.is_dir() {
+        Dataset::read_dir(path)
+    } else {
+        unpack(&fs::read(path)?).map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))
+    }
+}
+
+fn main() {
+    let mut paths = Vec::new();
+    let mut json = false;
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--json" => json = true,
+            _ => paths.push(arg),
+        }
+    }
+    let (old, new) = match &paths[..] {
+        [old, new] => (Path::new(old), Path::new(new)),
+        _ => {
+            eprintln!("usage: prun-diff OLD NEW [--json]");
+            process::exit(2);
+        }
+    };
+
+    let (old, new) = match (read(old), read(new)) {
+        (Ok(old), Ok(new)) => (old, new),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("error: {:#}", e);
+            process::exit(1);
+        }
+    };
+    let changes = diff(&old, &new);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&changes).expect("changes serialize"));
+        return;
+    }
+    let mut kind = None;
+    for change in &changes {
+        if kind != Some(change.kind) {
+            kind = Some(change.kind);
+            println!("{}", change.kind.label());
+        }
+        println!("  {}", change);
+    }
+    println!("{} changes", changes.len());
+}
@@ -0,0 +1,114 @@
+// Packs json/stars.json, planets.json and resources.json into the binary
+// universe.bin the app loads first (see pack.rs), and writes manifest.json
+// with the version the app caches the files under (see manifest.rs). The
+// manifest is only written when OUT_FILE is in JSON_DIR, as it hashes the
+// files the app fetches from there.
+//
+//     prun-pack [--as-of YYYY-MM-DD] [JSON_DIR] [OUT_FILE]
+//
+// The packed file is unpacked again and must give back the same records.
+// --as-of is the date the game data was taken, today if not given.
+// Neither output is kept in git: make data runs this before each build so
+// the manifest always hashes the universe.bin being served.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use prun_rusty_little_helper::dataset::{Dataset, MATERIALS_FILE, PLANETS_FILE, RESOURCES_FILE, STARS_FILE};
+use prun_rusty_little_helper::manifest::{Manifest, MANIFEST_FILE};
+use prun_rusty_little_helper::pack::{pack, unpack, PACK_FILE};
+
+// Every file the app fetches from the directory, bar the manifest itself
+const BASE_FILE: &str = "base.json";
+
+fn run(dir: PathBuf, out: PathBuf, as_of: String) -> anyhow::Result<()> {
+    let data = Dataset::read_dir(&dir)?;
+    let bytes = pack(&data)?;
+    if unpack(&bytes)? != data {
+        anyhow::bail!("packed data doesn't unpack as the records read");
+    }
+    fs::write(&out, &bytes)?;
+    let json_size: usize = data.to_files()?.iter().map(|(_, text)| text.len()).sum();
+    println!("{} bytes written to {} ({} bytes as JSON)", bytes.len(), out.display(), json_size);
+
+    if !is_in(&out, &dir)? {
+        println!("{} not written, {} isn't in {}", MANIFEST_FILE, out.display(), dir.display());
+        return Ok(());
+    }
+    let manifest = write_manifest(&dir, &bytes, as_of)?;
+    println!("{} written, version {}", dir.join(MANIFEST_FILE).display(), manifest.version);
+    Ok(())
+}
+
+// Whether the packed file is the one the app would fetch from the directory
+fn is_in(out: &Path, dir: &Path) -> anyhow::Result<bool> {
+    let parent = match out.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    Ok(out.file_name() == Some(PACK_FILE.as_ref()) && parent.canonicalize()? == dir.canonicalize()?)
+}
+
+fn write_manifest(dir: &Path, packed: &[u8], as_of: String) -> anyhow::Result<Manifest> {
+    let mut contents = Vec::new();
+    for file in [STARS_FILE, PLANETS_FILE, RESOURCES_FILE, BASE_FILE, MATERIALS_FILE] {
+        contents.push((file, fs::read(dir.join(file))?));
+    }
+    contents.push((PACK_FILE, packed.to_vec()));
+    let files: Vec<(&str, &[u8])> = contents.iter().map(|(f, b)| (*f, b.as_slice())).collect();
+    let manifest = Manifest::for_files(&files, as_of);
+    let mut text = serde_json::to_string_pretty(&manifest)?;
+    text.push('\n');
+    fs::write(dir.join(MANIFEST_FILE), text)?;
+    Ok(manifest)
+}
+
+// Today's UTC date as YYYY-MM-DD
+fn today() -> String {
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    // Days to civil date, after Howard Hinnant's days_from_civil inverse
+    let z = (secs / 86_400) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+fn is_date(s: &str) -> bool {
+    let parts: Vec<&str> = s.split('-').collect();
+    parts.len() == 3
+        && [4, 2, 2].iter().zip(&parts).all(|(len, p)| p.len() == *len && p.chars().all(|c| c.is_ascii_digit()))
+}
+
+fn usage() -> ! {
+    eprintln!("usage: prun-pack [--as-of YYYY-MM-DD] [JSON_DIR] [OUT_FILE]");
+    process::exit(2);
+}
+
+fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let mut as_of = today();
+    if let Some(i) = args.iter().position(|a| a == "--as-of") {
+        match args.get(i + 1) {
+            Some(date) if is_date(date) => as_of = date.clone(),
+            _ => usage(),
+        }
+        args.drain(i..i + 2);
+    }
+    let (dir, out) = match &args[..] {
+        [] => (PathBuf::from("json"), PathBuf::from("json").join(PACK_FILE)),
+        [dir] => (PathBuf::from(dir), PathBuf::from(dir).join(PACK_FILE)),
+        [dir, out] => (PathBuf::from(dir), PathBuf::from(out)),
+        _ => usage(),
+    };
+    if let Err(e) = run(dir, out, as_of) {
+        eprintln!("error: {:#}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,336 @@
+// Answers questions about json/*.json from the command line, with the same
+// model and filters as the app.
+//
+//     prun-query [--data DIR] [--json] COMMAND
+//
+//     search TEXT      systems and planets with TEXT in their id or name
+//     system SYSTEM    a system's planets and their resources
+//     filter [--surface S] [--grav G] [--temp T] [--pres P]
+//            [--res TICKER [--min FACTOR]] [QUERY...] [--csv]
+//     route FROM TO    the fewest jumps between two systems
+//     stats TICKER     how a resource is spread across the universe
+//
+// A system is a CX code, natural id or name. filter's options become terms of the
+// app's query text (see query.rs) and any QUERY is added as it is, eg.
+// 'jumps(ANT)<=5'. Prints tables, or JSON with --json.
+use serde_derive::Serialize;
+use std::io::{self, ErrorKind, Write};
+use std::path::Path;
+use std::process;
+
+use prun_rusty_little_helper::dataset::{read_json, Dataset, MATERIALS_FILE};
+use prun_rusty_little_helper::export::{filtered_planet_rows, hit_rows, planet_rows, to_csv, to_json, ExportRow};
+use prun_rusty_little_helper::graph::route;
+use prun_rusty_little_helper::models::{Material, Star, Universe};
+use prun_rusty_little_helper::query::parse;
+use prun_rusty_little_helper::validate::has_errors;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchHit {
+    kind: &'static str,
+    id: String,
+    name: String,
+    // The planet's system, empty for a system
+    system: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SystemInfo {
+    system: String,
+    name: String,
+    nearest_cx: Option<(&'static str, u32)>,
+    connections: Vec<String>,
+    planets: Vec<ExportRow>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RouteStop {
+    jumps: usize,
+    system: String,
+    name: String,
+}
+
+fn usage() -> ! {
+    eprintln!("usage: prun-query [--data DIR] [--json] COMMAND");
+    eprintln!("  search TEXT");
+    eprintln!("  system SYSTEM");
+    eprintln!("  filter [--surface S] [--grav G] [--temp T] [--pres P] [--res TICKER [--min FACTOR]] [QUERY...] [--csv]");
+    eprintln!("  route FROM TO");
+    eprintln!("  stats TICKER");
+    process::exit(2);
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("error: {}", message);
+    process::exit(1);
+}
+
+fn print_json<T: serde::Serialize>(out: &mut impl Write, value: &T) -> io::Result<()> {
+    writeln!(out, "{}", serde_json::to_string_pretty(value).expect("output serializes"))
+}
+
+// Columns padded to their widest cell
+fn print_table(out: &mut impl Write, header: &[&str], rows: &[Vec<String>]) -> io::Result<()> {
+    let mut widths: Vec<usize> = header.iter().map(|h| h.len()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let line = |cells: Vec<&str>| {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &width)| format!("{:width$}", cell, width = width))
+            .collect();
+        padded.join("  ").trim_end().to_string()
+    };
+    writeln!(out, "{}", line(header.to_vec()))?;
+    for row in rows {
+        writeln!(out, "{}", line(row.iter().map(String::as_str).collect()))?;
+    }
+    Ok(())
+}
+
+fn print_rows(out: &mut impl Write, rows: &[ExportRow]) -> io::Result<()> {
+    let header = ["planet", "name", "system", "surface", "grav", "temp", "pres", "fert", "ticker", "factor", "% max"];
+    let opt = |v: Option<f64>, places: usize| v.map(|v| format!("{:.*}", places, v)).unwrap_or_default();
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|r| vec![
+            r.planet.clone(),
+            r.planet_name.clone(),
+            r.system_name.clone(),
+            r.surface.clone(),
+            format!("{:.2}", r.gravity),
+            format!("{:.1}", r.temp),
+            format!("{:.2}", r.pressure),
+            if r.fertility > -1.0 { format!("{:+.0}%", r.fertility * 100.0) } else { "-".to_string() },
+            r.ticker.clone().unwrap_or_default(),
+            opt(r.factor, 3),
+            opt(r.percent_of_max, 0),
+        ])
+        .collect();
+    print_table(out, &header, &cells)
+}
+
+fn find_star<'a>(universe: &'a Universe, name: &str) -> &'a Star {
+    universe
+        .find_system(name.trim())
+        .unwrap_or_else(|| fail(&format!("no system '{}'", name)))
+}
+
+fn search(out: &mut impl Write, universe: &Universe, text: &str, json: bool) -> io::Result<()> {
+    let text = text.to_ascii_uppercase();
+    let matches = |id: &str, name: &str| {
+        id.to_ascii_uppercase().contains(&text) || name.to_ascii_uppercase().contains(&text)
+    };
+    let mut hits: Vec<SearchHit> = universe.stars
+        .iter()
+        .filter(|s| matches(&s.nat_id, &s.name))
+        .map(|s| SearchHit {
+            kind: "system",
+            id: s.nat_id.clone(),
+            name: s.name.clone(),
+            system: String::new(),
+        })
+        .collect();
+    hits.extend(universe.planets
+        .iter()
+        .filter(|p| matches(&p.nat_id, &p.name))
+        .map(|p| SearchHit {
+            kind: "planet",
+            id: p.nat_id.clone(),
+            name: p.name.clone(),
+            system: universe.star_for_planet(p).map(|s| s.name.clone()).unwrap_or_default(),
+        }));
+    if json {
+        return print_json(out, &hits);
+    }
+    let rows: Vec<Vec<String>> = hits
+        .into_iter()
+        .map(|h| vec![h.kind.to_string(), h.id, h.name, h.system])
+        .collect();
+    print_table(out, &["kind", "id", "name", "system"], &rows)
+}
+
+fn system(out: &mut impl Write, universe: &Universe, name: &str, json: bool) -> io::Result<()> {
+    let star = find_star(universe, name);
+    let info = SystemInfo {
+        system: star.nat_id.clone(),
+        name: star.name.clone(),
+        nearest_cx: universe.nearest_cx(star),
+        connections: universe.neighbours(star).iter().map(|s| s.nat_id.clone()).collect(),
+        planets: planet_rows(universe, |p| p.sys_id == star.sys_id),
+    };
+    if json {
+        return print_json(out, &info);
+    }
+    writeln!(out, "{} ({})", info.name, info.system)?;
+    match info.nearest_cx {
+        Some((code, jumps)) => writeln!(out, "Nearest CX {} in {} jumps", code, jumps)?,
+        None => writeln!(out, "Nearest CX none reachable")?,
+    }
+    writeln!(out, "Connections {}", info.connections.join(", "))?;
+    writeln!(out)?;
+    print_rows(out, &info.planets)
+}
+
+fn filter(
+    out: &mut impl Write,
+    universe: &Universe,
+    options: &[(String, String)],
+    terms: &[String],
+    json: bool,
+    csv: bool,
+) -> io::Result<()> {
+    let mut query = Vec::new();
+    let (mut res, mut min) = (None, None);
+    for (key, value) in options {
+        match key.as_str() {
+            "res" => res = Some(value),
+            "min" => min = Some(value),
+            key => query.push(format!("{}:{}", key, value)),
+        }
+    }
+    match (res, min) {
+        (Some(ticker), Some(min)) => query.push(format!("{}>={}", ticker, min)),
+        (Some(ticker), None) => query.push(ticker.clone()),
+        (None, Some(_)) => fail("--min needs --res"),
+        (None, None) => (),
+    }
+    query.extend(terms.iter().cloned());
+    let query = query.join(" ");
+    let parsed = parse(&query, &universe.res_list).unwrap_or_else(|e| fail(&format!("{} in '{}'", e, query)));
+
+    let results = universe.filter(&parsed.filters, &parsed.selected_res);
+    // With a resource, the planets having it best first, else every
+    // planet with all of its resources
+    let rows = match parsed.selected_res {
+        Some(_) => hit_rows(universe, &results),
+        None => filtered_planet_rows(universe, &results),
+    };
+    if json {
+        writeln!(out, "{}", to_json(&rows))
+    } else if csv {
+        write!(out, "{}", to_csv(&rows))
+    } else {
+        print_rows(out, &rows)?;
+        writeln!(out, "{} planets, {} rows", results.planets.len(), rows.len())
+    }
+}
+
+fn show_route(out: &mut impl Write, universe: &Universe, from: &str, to: &str, json: bool) -> io::Result<()> {
+    let (from, to) = (find_star(universe, from), find_star(universe, to));
+    let path = route(&universe.stars, &from.sys_id, &to.sys_id)
+        .unwrap_or_else(|| fail(&format!("no route from {} to {}", from.name, to.name)));
+    let stops: Vec<RouteStop> = path
+        .iter()
+        .enumerate()
+        .filter_map(|(jumps, sys_id)| {
+            let star = universe.stars.iter().find(|s| &s.sys_id == sys_id)?;
+            Some(RouteStop { jumps, system: star.nat_id.clone(), name: star.name.clone() })
+        })
+        .collect();
+    if json {
+        return print_json(out, &stops);
+    }
+    let rows: Vec<Vec<String>> = stops
+        .iter()
+        .map(|s| vec![s.jumps.to_string(), s.system.clone(), s.name.clone()])
+        .collect();
+    print_table(out, &["jumps", "system", "name"], &rows)
+}
+
+fn stats(out: &mut impl Write, universe: &Universe, ticker: &str, json: bool) -> io::Result<()> {
+    let ticker = ticker.to_ascii_uppercase();
+    let stats = universe.res_stats
+        .get(&ticker)
+        .unwrap_or_else(|| fail(&format!("no resource '{}'", ticker)));
+    if json {
+        return print_json(out, stats);
+    }
+    writeln!(out, "{} {}", stats.ticker, universe.material_name(&stats.ticker))?;
+    let rows = vec![
+        vec!["planets".to_string(), stats.planet_count.to_string()],
+        vec!["rocky".to_string(), stats.rocky.to_string()],
+        vec!["gaseous".to_string(), stats.gaseous.to_string()],
+        vec!["mean".to_string(), format!("{:.3}", stats.mean)],
+        vec!["p25".to_string(), format!("{:.3}", stats.p25)],
+        vec!["median".to_string(), format!("{:.3}", stats.median)],
+        vec!["p75".to_string(), format!("{:.3}", stats.p75)],
+        vec!["p90".to_string(), format!("{:.3}", stats.p90)],
+        vec!["max".to_string(), format!("{:.3}", stats.max)],
+        vec!["histogram".to_string(), format!("{:?}", stats.histogram)],
+    ];
+    print_table(out, &["", ""], &rows)?;
+    writeln!(out)?;
+    let top: Vec<Vec<String>> = stats.top
+        .iter()
+        .map(|(nat_id, factor)| {
+            let name = universe.planet_from_id(nat_id).map_or(nat_id.as_str(), |p| p.name.as_str());
+            vec![nat_id.clone(), name.to_string(), format!("{:.3}", factor)]
+        })
+        .collect();
+    print_table(out, &["planet", "name", "factor"], &top)
+}
+
+fn main() {
+    let mut dir = "json".to_string();
+    let mut json = false;
+    let mut csv = false;
+    // filter's options, without the --
+    let mut options = Vec::new();
+    let mut positional = Vec::new();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--csv" => csv = true,
+            "--data" => dir = args.next().unwrap_or_else(|| usage()),
+            "--surface" | "--grav" | "--temp" | "--pres" | "--res" | "--min" => {
+                let value = args.next().unwrap_or_else(|| usage());
+                options.push((arg[2..].to_string(), value));
+            }
+            a if a.starts_with("--") => usage(),
+            _ => positional.push(arg),
+        }
+    }
+    let (command, rest) = match positional.split_first() {
+        Some((command, rest)) => (command.as_str(), rest),
+        None => usage(),
+    };
+    if command != "filter" && (csv || !options.is_empty()) {
+        usage();
+    }
+
+    let data = Dataset::read_dir(Path::new(&dir)).unwrap_or_else(|e| fail(&format!("{:#}", e)));
+    let mut universe = Universe::from_dataset(data);
+    // Only for names, so not having it is no reason to stop
+    if let Ok(materials) = read_json::<Vec<Material>>(&Path::new(&dir).join(MATERIALS_FILE)) {
+        universe.materials = materials.into_iter().map(|m| (m.ticker.clone(), m)).collect();
+    }
+    if has_errors(&universe.data_issues) {
+        eprintln!("warning: problems with the data, see prun-validate {}", dir);
+    }
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let written = match (command, rest) {
+        ("search", [text]) if !text.trim().is_empty() => search(&mut out, &universe, text.trim(), json),
+        ("system", [name]) => system(&mut out, &universe, name, json),
+        ("filter", terms) => filter(&mut out, &universe, &options, terms, json, csv),
+        ("route", [from, to]) => show_route(&mut out, &universe, from, to, json),
+        ("stats", [ticker]) => stats(&mut out, &universe, ticker, json),
+        _ => usage(),
+    };
+    match written.and_then(|_| out.flush()) {
+        // eg. piped into head, which has all it wants
+        Err(e) if e.kind() == ErrorKind::BrokenPipe => (),
+        Err(e) => fail(&e.to_string()),
+        Ok(()) => (),
+    }
+}
@@ -0,0 +1,88 @@
+// Checks json/*.json before they go to the web server.
+//
+//     prun-validate [DIR] [--json] [--strict] [--schema N]
+//
+// Prints one line per problem, or a JSON array with --json. Exits 1 on
+// errors, and on warnings too with --strict. --strict also checks each
+// file has only the fields of its schema version (see schema.rs): the one
+// given, else the manifest's, else the latest.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use prun_rusty_little_helper::dataset::{read_json, Dataset, PLANETS_FILE, RESOURCES_FILE, STARS_FILE};
+use prun_rusty_little_helper::manifest::{Manifest, MANIFEST_FILE};
+use prun_rusty_little_helper::schema::{strict_issues, SCHEMA_VERSION};
+use prun_rusty_little_helper::validate::{has_errors, validate, Issue};
+
+fn schema_issues(dir: &Path, version: u32) -> anyhow::Result<Vec<Issue>> {
+    let mut issues = Vec::new();
+    for (file, name) in [(STARS_FILE, "stars"), (PLANETS_FILE, "planets"), (RESOURCES_FILE, "resources")] {
+        let records = serde_json::from_str(&fs::read_to_string(dir.join(file))?)?;
+        issues.extend(strict_issues(version, name, &records));
+    }
+    Ok(issues)
+}
+
+fn usage() -> ! {
+    eprintln!("usage: prun-validate [DIR] [--json] [--strict] [--schema N]");
+    process::exit(2);
+}
+
+fn main() {
+    let mut dir = PathBuf::from("json");
+    let mut json = false;
+    let mut strict = false;
+    let mut schema = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--strict" => strict = true,
+            "--schema" => match args.next().and_then(|n| n.parse().ok()) {
+                Some(n) => schema = Some(n),
+                None => usage(),
+            },
+            "-h" | "--help" => {
+                println!("usage: prun-validate [DIR] [--json] [--strict] [--schema N]");
+                return;
+            }
+            _ => dir = PathBuf::from(arg),
+        }
+    }
+
+    let data = match Dataset::read_dir(&dir) {
+        Ok(data) => data,
+        Err(e) => {
+            eprintln!("error: {:#}", e);
+            process::exit(2);
+        }
+    };
+    let mut issues = validate(&data.stars, &data.planets, &data.resources);
+    if strict {
+        let version = schema.unwrap_or_else(|| {
+            read_json::<Manifest>(&dir.join(MANIFEST_FILE)).map_or(SCHEMA_VERSION, |m| m.schema_version)
+        });
+        match schema_issues(&dir, version) {
+            Ok(schema_issues) => issues.extend(schema_issues),
+            Err(e) => {
+                eprintln!("error: {:#}", e);
+                process::exit(2);
+            }
+        }
+    }
+    if json {
+        println!("{}", serde_json::to_string_pretty(&issues).expect("issues serialize"));
+    } else {
+        for issue in &issues {
+            println!("{}", issue);
+        }
+        println!(
+            "{} stars, {} planets, {} resources: {} problems",
+            data.stars.len(), data.planets.len(), data.resources.len(), issues.len()
+        );
+    }
+    if has_errors(&issues) || (strict && !issues.is_empty()) {
+        process::exit(1);
+    }
+}
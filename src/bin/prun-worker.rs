@@ -0,0 +1,8 @@
+// The web worker the app filters in (see filter_worker.rs). Only of use
+// built for wasm32 and loaded by the page, which index.html has trunk do.
+use prun_rusty_little_helper::filter_worker::FilterWorker;
+use yew::agent::Threaded;
+
+pub fn main() {
+    FilterWorker::register();
+}
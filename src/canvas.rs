@@ -1,9 +1,13 @@
 use yew::prelude::*;
 use std::f64;
-use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen::JsCast;
 use web_sys::{HtmlCanvasElement, CanvasRenderingContext2d};
 
-use crate::models::{MapFeatures, Star, Universe};
+use std::collections::HashSet;
+
+use crate::favourites::Favourites;
+use crate::models::{CxDistance, MapFeatures, Star, Universe, View, CX_LIST};
+use crate::shared::Shared;
 
 #[derive(PartialEq, Clone, Debug)]
 pub struct Map {
@@ -27,9 +31,19 @@ impl Map {
 #[derive(Clone, PartialEq, Properties)]
 pub struct Props {
     pub map_features: MapFeatures,
-    pub universe: Universe,
+    pub universe: Shared<Universe>,
+    pub view: View,
     pub env_filter: bool,
+    pub favourites: Favourites,
+    // Planet nat_ids
+    pub bases: Vec<String>,
+    // sys_ids changed since a loaded snapshot
+    pub changed_systems: HashSet<String>,
+    // The planet open in the planet view, and those pinned to compare
+    pub selected_planet: Option<String>,
+    pub pinned: Vec<String>,
     pub selected_star_signal: Callback<Star>,
+    pub pin_signal: Callback<String>,
 }
 
 #[allow(dead_code)]
@@ -38,14 +52,14 @@ pub struct Canvas {
     map: Map,
     current_star: Star,
     //last_selected_star: Star,
-    cx: Vec<&'static str>,
     link: ComponentLink<Self>,
     props: Props,
 }
 
 pub enum Msg {
     MouseDown(MouseEvent),
-    MouseMove(MouseEvent)
+    MouseMove(MouseEvent),
+    Pin(String),
 }
 
 struct StarColour;
@@ -57,6 +71,10 @@ impl StarColour {
     pub const LAST_STAR_HALO: &'static str = "rgba(233,30,99,1.0)";
     pub const SELECTED_STAR_HALO: &'static str = "rgba(255,235,59,1.0)";
     pub const CURRENT: &'static str = "#2196f3";
+    pub const FAVOURITE: &'static str = "rgba(255,193,7,1.0)";
+    pub const BASE: &'static str = "rgba(156,39,176,1.0)";
+    pub const REACH: &'static str = "rgba(0,188,212,0.15)";
+    pub const CHANGED: &'static str = "rgba(255,87,34,1.0)";
 }
 
 struct StarSize;
@@ -66,6 +84,10 @@ impl StarSize {
     pub const SMALL: f64 = 8.0;
     pub const LARGE: f64 = 10.0;
     pub const CX: f64 = 17.0;
+    pub const FAVOURITE: f64 = 14.0;
+    pub const BASE: f64 = 22.0;
+    pub const REACH: f64 = 40.0;
+    pub const CHANGED: f64 = 27.0;
 }
 
 #[allow(dead_code)]
@@ -73,9 +95,29 @@ enum Fill {
     Filled,
     Halo,
     Cx,
+    Base,
 }
 
 impl Canvas {
+    // Pin the planet being looked at without leaving the map
+    fn view_pin(&self) -> Html {
+        let p = match self.props.selected_planet.as_ref().and_then(|id| self.props.universe.planet_from_id(id)) {
+            Some(p) => p,
+            None => return html! {},
+        };
+        let pinned = self.props.pinned.contains(&p.nat_id);
+        let nat_id = p.nat_id.clone();
+        html! {
+            <button
+                class="map-pin"
+                title="Pin to compare"
+                onclick=self.link.callback(move |_| Msg::Pin(nat_id.clone()))
+            >
+                <i class=classes!("fas", "fa-thumbtack", "pin", pinned.then_some("pinned")) />
+                {format!(" {} {}", if pinned { "Unpin" } else { "Pin" }, p.name)}
+            </button>
+        }
+    }
 
     fn get_ctx(&self) -> CanvasRenderingContext2d {
         let canvas: HtmlCanvasElement = self.canvas.cast().unwrap();
@@ -94,12 +136,12 @@ impl Canvas {
             Fill::Filled => {
                 ctx.begin_path();
                 ctx.arc(x, y, size, 0.0, 2.0 * f64::consts::PI).unwrap();
-                ctx.set_fill_style(&JsValue::from_str(col));
+                ctx.set_fill_style_str(col);
                 ctx.fill();
             }
             Fill::Halo => {
                 ctx.set_line_width(size);
-                ctx.set_stroke_style(&JsValue::from_str(col));
+                ctx.set_stroke_style_str(col);
                 for i in 0..4 {
                     let j = i as f64;
                     ctx.begin_path();
@@ -112,12 +154,17 @@ impl Canvas {
             }
             Fill::Cx => {
                 ctx.set_line_width(2.0);
-                ctx.set_stroke_style(&JsValue::from_str(col));
+                ctx.set_stroke_style_str(col);
                 ctx.begin_path();
                 ctx.arc(x, y, size, 0.0, 2.0 * f64::consts::PI).unwrap();
-                ctx.set_fill_style(&JsValue::from_str(col));
+                ctx.set_fill_style_str(col);
                 ctx.stroke();
             }
+            Fill::Base => {
+                ctx.set_line_width(3.0);
+                ctx.set_stroke_style_str(col);
+                ctx.stroke_rect(x - size / 2.0, y - size / 2.0, size, size);
+            }
         }
     }
 
@@ -127,6 +174,15 @@ impl Canvas {
         let height = self.map.height;
         let scale = self.props.map_features.selected_scale;
         let show_cx = self.props.map_features.show_cx;
+        let favourite_systems: HashSet<&str> = self.props.favourites
+            .values()
+            .map(|f| f.system.as_str())
+            .collect();
+        let base_systems: HashSet<&str> = self.props.bases
+            .iter()
+            .filter_map(|b| self.props.universe.planet_from_id(b))
+            .map(|p| p.sys_id.as_str())
+            .collect();
         let offset_x = self.map.offset_x;
         let offset_y = self.map.offset_y;
 
@@ -136,31 +192,53 @@ impl Canvas {
         ctx.translate(offset_x, offset_y).unwrap();
         ctx.clear_rect(0.0-offset_x, 0.0-offset_y, width/scale, height/scale);
 
+        // The reachable region goes underneath everything else
+        if let Some(reachable) = &self.props.view.results.reachable {
+            for star in self.props.universe.stars.iter().filter(|s| reachable.contains(&s.sys_id)) {
+                self.draw_star_circle(&ctx, star.x.round(), star.y.round(), StarSize::REACH,
+                    StarColour::REACH, Fill::Filled);
+            }
+        }
+
         for star in &self.props.universe.stars {
             let x = star.x.round();
             let y = star.y.round();
 
             let is_current = self.current_star.sys_id == star.sys_id;
-            let is_selected = match &self.props.universe.selected_star {
+            let is_selected = match &self.props.view.selected_star {
                 Some(s) => s.sys_id == star.sys_id,
                 None => false,
             };
             //let is_last_selected = self.last_selected_star.sys_id == star.sys_id;
 
-            if show_cx && self.cx.iter().any(|&i| i == star.nat_id) {
+            if show_cx && CX_LIST.iter().any(|cx| cx.nat_id == star.nat_id) {
                  self.draw_star_circle(&ctx, x, y, StarSize::CX,
                     StarColour::LAST_STAR_HALO, Fill::Cx);
             }
 
-            let res_max_factor = &self.props.universe.res_max_factor;
-            let selected_res = &self.props.universe.selected_res;
+            if favourite_systems.contains(star.nat_id.as_str()) {
+                self.draw_star_circle(&ctx, x, y, StarSize::FAVOURITE,
+                    StarColour::FAVOURITE, Fill::Cx);
+            }
+
+            if self.props.changed_systems.contains(&star.sys_id) {
+                self.draw_star_circle(&ctx, x, y, StarSize::CHANGED,
+                    StarColour::CHANGED, Fill::Cx);
+            }
 
-            if star.res_factor.gt(&0.0) {
+            if base_systems.contains(star.sys_id.as_str()) {
+                self.draw_star_circle(&ctx, x, y, StarSize::BASE,
+                    StarColour::BASE, Fill::Base);
+            }
+
+            let universe = &self.props.universe;
+            let selected_res = &self.props.view.selected_res;
+            let res_factor = self.props.view.star_factor(star);
+
+            if res_factor.gt(&0.0) {
                 match selected_res {
                     Some(res) => {
-                        let max_factor =
-                            res_max_factor.get(res).unwrap();
-                        let conc = star.res_factor/max_factor;
+                        let conc = universe.concentration(res, res_factor, self.props.view.normalisation);
                         let colour = match conc {
                             c if c >= 0.66 => "#4caf50",
                             c if c >= 0.33 => "#ff9800",
@@ -189,13 +267,13 @@ impl Canvas {
                 let s = format!("{} (Type {})", star.name, star.typ);
 
                 ctx.set_font("40px 'Open Sans'");
-                ctx.set_fill_style(&JsValue::from_str("rgba(255,255,255,1.0"));
+                ctx.set_fill_style_str("rgba(255,255,255,1.0");
                 ctx.fill_text(&s, x + 20.0, y - 15.0).unwrap();
                 let tm = ctx.measure_text(&s).unwrap();
-                ctx.set_fill_style(&JsValue::from_str("rgba(158,158,158,0.15"));
+                ctx.set_fill_style_str("rgba(158,158,158,0.15");
                 ctx.fill_rect(x + 5.0, y - 70.0, tm.width() + 30.0, 80.0);
                 ctx.set_line_width(2.0);
-                ctx.set_stroke_style(&JsValue::from_str("rgba(255,255,255,0.3"));
+                ctx.set_stroke_style_str("rgba(255,255,255,0.3");
                 ctx.stroke_rect(x + 5.0, y - 70.0, tm.width() + 30.0, 80.0);
             }
 
@@ -203,8 +281,14 @@ impl Canvas {
                 self.draw_star_circle(&ctx, x, y, StarSize::SMALL,
                     StarColour::CURRENT, Fill::Filled);
                 ctx.set_font("40px 'Open Sans'");
-                ctx.set_fill_style(&JsValue::from_str("rgba(255,255,255,1.0"));
-                ctx.fill_text(&star.name, x + 20.0, y - 15.0).unwrap();
+                ctx.set_fill_style_str("rgba(255,255,255,1.0");
+                let label = match self.props.universe.cx_distances.get(&star.sys_id) {
+                    Some(CxDistance { jumps: Some((code, jumps)), .. }) => {
+                        format!("{} ({} {}j)", star.name, code, jumps)
+                    }
+                    _ => star.name.clone(),
+                };
+                ctx.fill_text(&label, x + 20.0, y - 15.0).unwrap();
             }
         }
     }
@@ -220,7 +304,6 @@ impl Component for Canvas {
             map: Map::new(),
             current_star: Star::new(),
             //last_selected_star: Star::new(),
-            cx: vec!["OT-580", "UV-351", "VH-331", "ZV-307"],
             link,
             props,
         }
@@ -252,12 +335,12 @@ impl Component for Canvas {
                 let mut distance = 0.0;
                 let canvas: HtmlCanvasElement = self.canvas.cast().unwrap();
                 let bx = canvas.get_bounding_client_rect();
-                let x1 = (e.client_x() as f64 - bx.left()) as f64 /scale - offset_x;
-                let y1 = (e.client_y() as f64 - bx.top()) as f64 /scale - offset_y;
+                let x1 = (e.client_x() as f64 - bx.left()) /scale - offset_x;
+                let y1 = (e.client_y() as f64 - bx.top()) /scale - offset_y;
 
                 for star in &self.props.universe.stars {
                     let d = (star.x - x1).powf(2.0) + (star.y - y1).powf(2.0);
-                    if best_fit == None || d < distance {
+                    if best_fit.is_none() || d < distance {
                         best_fit = Some(star);
                         distance = d;
                     };
@@ -265,6 +348,7 @@ impl Component for Canvas {
                 self.current_star = best_fit.unwrap().clone();
                 self.draw();
             }
+            Msg::Pin(nat_id) => self.props.pin_signal.emit(nat_id),
         }
         false
     }
@@ -279,6 +363,7 @@ impl Component for Canvas {
                     onmousedown=self.link.callback(Msg::MouseDown)
                     onmousemove=self.link.callback(Msg::MouseMove)
                 />
+                {self.view_pin()}
             </div>
         }
     }
@@ -0,0 +1,249 @@
+use std::cmp::Ordering;
+use yew::prelude::*;
+
+use crate::extraction::{daily_output, ExtractionSettings};
+use crate::models::{Environment, Planet, Universe};
+use crate::shared::Shared;
+
+pub const MAX_PINNED: usize = 5;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    pub universe: Shared<Universe>,
+    pub pinned: Vec<String>,
+    pub extraction: ExtractionSettings,
+    pub unpin_signal: Callback<String>,
+    pub search_star_signal: Callback<String>,
+}
+
+pub enum Msg {
+    Unpin(String),
+    SelectPlanet(String),
+}
+
+#[allow(dead_code)]
+pub struct PlanetCompare {
+    link: ComponentLink<Self>,
+    props: Props,
+}
+
+// Which way is better for a row, so the best cell can be picked out
+#[derive(Clone, Copy)]
+enum Better {
+    Higher,
+    Lower,
+    None,
+}
+
+struct CompareRow {
+    label: String,
+    // Text and the value compared on, per pinned planet
+    cells: Vec<(String, Option<f64>)>,
+    better: Better,
+}
+
+impl CompareRow {
+    // Nothing to pick out until there are two planets to compare
+    fn best(&self) -> Option<f64> {
+        if self.cells.len() < 2 {
+            return None;
+        }
+        let values = self.cells.iter().filter_map(|(_, v)| *v);
+        let by_f64 = |a: &f64, b: &f64| a.partial_cmp(b).unwrap_or(Ordering::Equal);
+        match self.better {
+            Better::Higher => values.max_by(by_f64),
+            Better::Lower => values.min_by(by_f64),
+            Better::None => None,
+        }
+    }
+}
+
+// How far outside the normal range, 0 when normal
+fn env_excess(p: &Planet, env: &Environment) -> f64 {
+    let (min, max) = env.normal_range();
+    let v = p.env_value(env);
+    (min - v).max(v - max).max(0.0) / (max - min)
+}
+
+impl PlanetCompare {
+    fn rows(&self, planets: &[&Planet]) -> Vec<CompareRow> {
+        let universe = &self.props.universe;
+        let mut rows = Vec::new();
+        let row = |label: &str, better: Better, cell: &dyn Fn(&Planet) -> (String, Option<f64>)| {
+            CompareRow {
+                label: label.to_string(),
+                cells: planets.iter().map(|p| cell(p)).collect(),
+                better,
+            }
+        };
+
+        rows.push(row("System", Better::None, &|p| {
+            let name = universe.star_for_planet(p).map(|s| s.name.clone()).unwrap_or_default();
+            (name, None)
+        }));
+        rows.push(row("Surface", Better::None, &|p| {
+            (if p.surface { "Rocky" } else { "Gaseous" }.to_string(), None)
+        }));
+        let envs = [
+            (Environment::Gravity, "Gravity", 2),
+            (Environment::Temp, "Temperature", 0),
+            (Environment::Pressure, "Pressure", 2),
+        ];
+        for (env, label, dp) in envs.iter() {
+            rows.push(row(label, Better::Lower, &|p| {
+                let text = format!("{:.*} {}", *dp, p.env_value(env), p.env_level(env));
+                (text, Some(env_excess(p, env)))
+            }));
+        }
+        rows.push(row("Fertility", Better::Higher, &|p| {
+            (p.fertility_text(), if p.is_fertile() { Some(p.fertility) } else { None })
+        }));
+        rows.push(row("Nearest CX", Better::Lower, &|p| {
+            match universe.star_for_planet(p).and_then(|s| universe.nearest_cx(s)) {
+                Some((code, jumps)) => (format!("{} {} jumps", code, jumps), Some(jumps as f64)),
+                None => ("-".to_string(), None),
+            }
+        }));
+
+        // Every resource on any of the planets, factor and daily output
+        let mut tickers: Vec<String> = planets
+            .iter()
+            .flat_map(|p| universe.resources_for_planet(p))
+            .map(|r| r.ticker)
+            .collect();
+        tickers.sort();
+        tickers.dedup();
+        for ticker in &tickers {
+            rows.push(row(ticker, Better::Higher, &|p| {
+                let resources = universe.resources_for_planet(p);
+                match resources.iter().find(|r| &r.ticker == ticker) {
+                    Some(r) => {
+                        let per_day = daily_output(r, &self.props.extraction);
+                        (format!("{:.2} ({:.1}/d)", r.factor, per_day), Some(per_day))
+                    }
+                    None => ("-".to_string(), None),
+                }
+            }));
+        }
+
+        // The base bill, material by material
+        let bills: Vec<_> = planets.iter().map(|p| universe.base_bill(p)).collect();
+        let mut materials: Vec<String> = Vec::new();
+        for m in bills.iter().flatten() {
+            if !materials.contains(&m.ticker) {
+                materials.push(m.ticker.clone());
+            }
+        }
+        let loads: Vec<_> = bills.iter().map(|bill| universe.bill_load(bill)).collect();
+        let load_row = |label: &str, unit: &str, part: &dyn Fn((f64, f64)) -> f64| CompareRow {
+            label: label.to_string(),
+            cells: loads
+                .iter()
+                .map(|load| match load {
+                    Some(load) => (format!("{:.1} {}", part(*load), unit), Some(part(*load))),
+                    None => ("-".to_string(), None),
+                })
+                .collect(),
+            better: Better::Lower,
+        };
+        rows.push(load_row("Base weight", "t", &|(weight, _)| weight));
+        rows.push(load_row("Base volume", "m³", &|(_, volume)| volume));
+        for ticker in &materials {
+            rows.push(CompareRow {
+                label: format!("Base {}", ticker),
+                cells: bills
+                    .iter()
+                    .map(|bill| {
+                        let amount = bill.iter().find(|m| &m.ticker == ticker).map_or(0, |m| m.amount);
+                        (amount.to_string(), Some(amount as f64))
+                    })
+                    .collect(),
+                better: Better::Lower,
+            });
+        }
+        rows
+    }
+
+    fn view_row(&self, row: &CompareRow) -> Html {
+        let best = row.best();
+        html! {
+            <tr>
+                <td>{&row.label}</td>
+                { for row.cells.iter().map(|(text, value)| {
+                    let class = match (best, value) {
+                        (Some(best), Some(v)) if (best - v).abs() < f64::EPSILON => "best",
+                        (Some(_), _) => "worse",
+                        _ => "",
+                    };
+                    html! { <td class=class>{text}</td> }
+                })}
+            </tr>
+        }
+    }
+}
+
+impl Component for PlanetCompare {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Self { link, props }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> bool {
+        if self.props != props {
+            self.props = props;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Unpin(nat_id) => self.props.unpin_signal.emit(nat_id),
+            Msg::SelectPlanet(nat_id) => self.props.search_star_signal.emit(nat_id),
+        }
+        false
+    }
+
+    fn view(&self) -> Html {
+        let universe = &self.props.universe;
+        let planets: Vec<&Planet> = self.props.pinned
+            .iter()
+            .filter_map(|id| universe.planet_from_id(id))
+            .collect();
+        if planets.is_empty() {
+            return html! {};
+        }
+        html! {
+            <div class="planet-compare">
+                <h3>{"Compare planets"}</h3>
+                <table>
+                    <tr>
+                        <th></th>
+                        { for planets.iter().map(|p| {
+                            let select = p.nat_id.clone();
+                            let unpin = p.nat_id.clone();
+                            html! {
+                                <th>
+                                    <span
+                                        class="link"
+                                        onclick=self.link.callback(move |_| Msg::SelectPlanet(select.clone()))
+                                    >
+                                        {&p.name}
+                                    </span>
+                                    <i
+                                        class="fas fa-times unpin"
+                                        onclick=self.link.callback(move |_| Msg::Unpin(unpin.clone()))
+                                    />
+                                </th>
+                            }
+                        })}
+                    </tr>
+                    { for self.rows(&planets).iter().map(|row| self.view_row(row)) }
+                </table>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,127 @@
+// The data files kept in the browser's Cache API under the manifest
+// version, so they're fetched once per version and the app still loads
+// with no network. Caches left from older versions are pruned.
+use js_sys::{Array, Uint8Array};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::{Cache, CacheStorage, RequestCache, RequestInit, Response};
+use yew::format::Json;
+use yew::services::storage::{Area, StorageService};
+use yew::Callback;
+
+use crate::manifest::Manifest;
+
+const CACHE_PREFIX: &str = "prun-data-";
+
+// Without a version there's nothing to say a cached copy is current, so
+// the file comes straight from the network.
+pub fn fetch_cached(url: String, version: Option<String>, callback: Callback<Result<Vec<u8>, anyhow::Error>>) {
+    spawn_local(async move {
+        let result = fetch(&url, version.as_deref()).await;
+        callback.emit(result.map_err(|e| anyhow::anyhow!("{}: {}", url, describe(e))));
+    });
+}
+
+// Drop the caches of every version but this one
+pub fn prune(version: String) {
+    spawn_local(async move {
+        if let Some(caches) = caches() {
+            if let Ok(keys) = JsFuture::from(caches.keys()).await {
+                let current = cache_name(&version);
+                for key in Array::from(&keys).iter().filter_map(|k| k.as_string()) {
+                    if key.starts_with(CACHE_PREFIX) && key != current {
+                        let _ = JsFuture::from(caches.delete(&key)).await;
+                    }
+                }
+            }
+        }
+    });
+}
+
+async fn fetch(url: &str, version: Option<&str>) -> Result<Vec<u8>, JsValue> {
+    let window = web_sys::window().ok_or("no window")?;
+    let cache = match version {
+        Some(version) => open(version).await,
+        None => None,
+    };
+    if let Some(cache) = &cache {
+        let hit = JsFuture::from(cache.match_with_str(url)).await?;
+        if !hit.is_undefined() {
+            return body(hit.dyn_into()?).await;
+        }
+    }
+    // Revalidate with the server (ETag) rather than trust the HTTP cache,
+    // as a new version means the files have changed
+    let init = RequestInit::new();
+    init.set_cache(RequestCache::NoCache);
+    let resp: Response = JsFuture::from(window.fetch_with_str_and_init(url, &init)).await?.dyn_into()?;
+    if !resp.ok() {
+        return Err(format!("status {}", resp.status()).into());
+    }
+    if let Some(cache) = &cache {
+        // A failed put only costs a fetch next time
+        let _ = JsFuture::from(cache.put_with_str(url, &resp.clone()?)).await;
+    }
+    body(resp).await
+}
+
+async fn body(resp: Response) -> Result<Vec<u8>, JsValue> {
+    let buffer = JsFuture::from(resp.array_buffer()?).await?;
+    Ok(Uint8Array::new(&buffer).to_vec())
+}
+
+// The Cache API is only there in secure contexts (https or localhost)
+fn caches() -> Option<CacheStorage> {
+    web_sys::window()?.caches().ok()
+}
+
+async fn open(version: &str) -> Option<Cache> {
+    let cache = JsFuture::from(caches()?.open(&cache_name(version))).await.ok()?;
+    cache.dyn_into().ok()
+}
+
+fn cache_name(version: &str) -> String {
+    format!("{}{}", CACHE_PREFIX, version)
+}
+
+fn describe(e: JsValue) -> String {
+    e.as_string().unwrap_or_else(|| format!("{:?}", e))
+}
+
+const MANIFEST_KEY: &str = "prun.manifest";
+
+// The last manifest fetched, kept in localStorage so the cached files can
+// still be found when the manifest itself can't be fetched.
+pub struct ManifestStore {
+    storage: Option<StorageService>,
+    manifest: Option<Manifest>,
+}
+
+impl ManifestStore {
+    pub fn new() -> Self {
+        let storage = StorageService::new(Area::Local).ok();
+        let manifest = storage.as_ref().and_then(|s| {
+            let Json(manifest) = s.restore(MANIFEST_KEY);
+            manifest.ok()
+        });
+        Self { storage, manifest }
+    }
+    pub fn get(&self) -> Option<&Manifest> {
+        self.manifest.as_ref()
+    }
+    pub fn version(&self) -> Option<String> {
+        self.manifest.as_ref().map(|m| m.version.clone())
+    }
+    pub fn set(&mut self, manifest: Manifest) {
+        if let Some(storage) = &mut self.storage {
+            storage.store(MANIFEST_KEY, Json(&manifest));
+        }
+        self.manifest = Some(manifest);
+    }
+}
+
+impl Default for ManifestStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
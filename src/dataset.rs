@@ -0,0 +1,52 @@
+// The three data files read together from a directory, for the native
+// tools. The web app fetches them one by one instead.
+use anyhow::Context;
+use serde::de::DeserializeOwned;
+use std::fs;
+use std::path::Path;
+
+use crate::models::{Planet, Resource, Star};
+
+pub const STARS_FILE: &str = "stars.json";
+pub const PLANETS_FILE: &str = "planets.json";
+pub const RESOURCES_FILE: &str = "resources.json";
+// The material catalogue, kept apart from the records above
+pub const MATERIALS_FILE: &str = "materials.json";
+
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct Dataset {
+    pub stars: Vec<Star>,
+    pub planets: Vec<Planet>,
+    pub resources: Vec<Resource>,
+}
+
+impl Dataset {
+    pub fn read_dir(dir: &Path) -> anyhow::Result<Self> {
+        Ok(Self {
+            stars: read_json(&dir.join(STARS_FILE))?,
+            planets: read_json(&dir.join(PLANETS_FILE))?,
+            resources: read_json(&dir.join(RESOURCES_FILE))?,
+        })
+    }
+    // The files exactly as the app fetches them
+    pub fn to_files(&self) -> anyhow::Result<Vec<(&'static str, String)>> {
+        Ok(vec![
+            (STARS_FILE, serde_json::to_string(&self.stars)?),
+            (PLANETS_FILE, serde_json::to_string(&self.planets)?),
+            (RESOURCES_FILE, serde_json::to_string(&self.resources)?),
+        ])
+    }
+    pub fn write_dir(&self, dir: &Path) -> anyhow::Result<()> {
+        fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        for (file, text) in self.to_files()? {
+            let path = dir.join(file);
+            fs::write(&path, text).with_context(|| format!("writing {}", path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> anyhow::Result<T> {
+    let text = fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    serde_json::from_str(&text).with_context(|| format!("parsing {}", path.display()))
+}
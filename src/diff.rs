@@ -0,0 +1,336 @@
+// What changed between two snapshots of the data, eg. before and after a
+// refresh from FIO. Records are matched by id (sys_id for systems, nat_id
+// for planets, planet and ticker for resources), so a renamed planet is a
+// change rather than one removed and another added.
+use serde_derive::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
+
+use crate::dataset::Dataset;
+use crate::models::{Planet, Star};
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum ChangeKind {
+    AddedSystem,
+    RemovedSystem,
+    AddedPlanet,
+    RemovedPlanet,
+    RenamedPlanet,
+    Environment,
+    Factor,
+    Connections,
+}
+
+impl ChangeKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ChangeKind::AddedSystem => "Added systems",
+            ChangeKind::RemovedSystem => "Removed systems",
+            ChangeKind::AddedPlanet => "Added planets",
+            ChangeKind::RemovedPlanet => "Removed planets",
+            ChangeKind::RenamedPlanet => "Renamed planets",
+            ChangeKind::Environment => "Changed environments",
+            ChangeKind::Factor => "Changed factors",
+            ChangeKind::Connections => "Changed connections",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct Change {
+    pub kind: ChangeKind,
+    // The system it's in, by sys_id, for marking on the map. Empty for a
+    // resource on a planet neither snapshot has.
+    pub sys_id: String,
+    // The record changed, eg. a planet's nat_id
+    pub id: String,
+    pub message: String,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.id, self.message)
+    }
+}
+
+pub fn diff(old: &Dataset, new: &Dataset) -> Vec<Change> {
+    let mut changes = Vec::new();
+    let mut change = |kind, sys_id: &str, id: &str, message: String| {
+        changes.push(Change { kind, sys_id: sys_id.to_string(), id: id.to_string(), message });
+    };
+
+    let old_stars: HashMap<&str, &Star> = old.stars.iter().map(|s| (s.sys_id.as_str(), s)).collect();
+    let new_stars: HashMap<&str, &Star> = new.stars.iter().map(|s| (s.sys_id.as_str(), s)).collect();
+    // Connections are shown by nat_id, from whichever snapshot has the system
+    let star_name = |sys_id: &str| {
+        new_stars.get(sys_id).or_else(|| old_stars.get(sys_id))
+            .map_or_else(|| sys_id.to_string(), |s| s.nat_id.clone())
+    };
+    for s in &new.stars {
+        match old_stars.get(s.sys_id.as_str()) {
+            None => change(ChangeKind::AddedSystem, &s.sys_id, &s.nat_id, named("added", &s.name, &s.nat_id)),
+            Some(was) => {
+                let before: HashSet<&str> = was.connections.iter().map(|c| c.connection.as_str()).collect();
+                let after: HashSet<&str> = s.connections.iter().map(|c| c.connection.as_str()).collect();
+                let mut added: Vec<String> = after.difference(&before).map(|c| star_name(c)).collect();
+                let mut removed: Vec<String> = before.difference(&after).map(|c| star_name(c)).collect();
+                added.sort();
+                removed.sort();
+                let mut parts = Vec::new();
+                if !added.is_empty() {
+                    parts.push(format!("now connects to {}", added.join(", ")));
+                }
+                if !removed.is_empty() {
+                    parts.push(format!("no longer connects to {}", removed.join(", ")));
+                }
+                if !parts.is_empty() {
+                    change(ChangeKind::Connections, &s.sys_id, &s.nat_id, parts.join("; "));
+                }
+            }
+        }
+    }
+    for s in &old.stars {
+        if !new_stars.contains_key(s.sys_id.as_str()) {
+            change(ChangeKind::RemovedSystem, &s.sys_id, &s.nat_id, named("removed", &s.name, &s.nat_id));
+        }
+    }
+
+    let old_planets: HashMap<&str, &Planet> = old.planets.iter().map(|p| (p.nat_id.as_str(), p)).collect();
+    let new_planets: HashMap<&str, &Planet> = new.planets.iter().map(|p| (p.nat_id.as_str(), p)).collect();
+    for p in &new.planets {
+        let was = match old_planets.get(p.nat_id.as_str()) {
+            Some(was) => was,
+            None => {
+                change(ChangeKind::AddedPlanet, &p.sys_id, &p.nat_id, named("added", &p.name, &p.nat_id));
+                continue;
+            }
+        };
+        // Unnamed planets go by their nat_id
+        if was.name != p.name {
+            let message = if was.name == was.nat_id {
+                format!("named {}", p.name)
+            } else {
+                format!("renamed from {} to {}", was.name, p.name)
+            };
+            change(ChangeKind::RenamedPlanet, &p.sys_id, &p.nat_id, message);
+        }
+        let mut parts = Vec::new();
+        if was.surface != p.surface {
+            parts.push(format!("surface {} to {}", was.surface, p.surface));
+        }
+        let values = [
+            ("gravity", was.gravity, p.gravity),
+            ("temp", was.temp, p.temp),
+            ("pressure", was.pressure, p.pressure),
+            ("fertility", was.fertility, p.fertility),
+        ];
+        for (name, before, after) in values.iter() {
+            if before != after {
+                parts.push(format!("{} {:.2} to {:.2}", name, before, after));
+            }
+        }
+        if !parts.is_empty() {
+            change(ChangeKind::Environment, &p.sys_id, &p.nat_id, parts.join(", "));
+        }
+    }
+    for p in &old.planets {
+        if !new_planets.contains_key(p.nat_id.as_str()) {
+            change(ChangeKind::RemovedPlanet, &p.sys_id, &p.nat_id, named("removed", &p.name, &p.nat_id));
+        }
+    }
+
+    // A resource found or lost counts as a factor changing from or to none
+    let factors = |data: &Dataset| -> BTreeMap<(String, String), f64> {
+        data.resources.iter().map(|r| ((r.planet.clone(), r.ticker.clone()), r.factor)).collect()
+    };
+    let (old_factors, new_factors) = (factors(old), factors(new));
+    let keys: BTreeSet<&(String, String)> = old_factors.keys().chain(new_factors.keys()).collect();
+    for key in keys {
+        let (planet, ticker) = key;
+        let message = match (old_factors.get(key), new_factors.get(key)) {
+            (Some(before), Some(after)) if before != after => {
+                format!("{} factor {:.4} to {:.4}", ticker, before, after)
+            }
+            (None, Some(after)) => format!("{} found, factor {:.4}", ticker, after),
+            (Some(before), None) => format!("{} gone, factor was {:.4}", ticker, before),
+            _ => continue,
+        };
+        let sys_id = new_planets.get(planet.as_str())
+            .or_else(|| old_planets.get(planet.as_str()))
+            .map_or("", |p| p.sys_id.as_str());
+        change(ChangeKind::Factor, sys_id, planet, message);
+    }
+
+    changes.sort_by(|a, b| (a.kind, &a.id).cmp(&(b.kind, &b.id)));
+    changes
+}
+
+fn named(what: &str, name: &str, nat_id: &str) -> String {
+    if name == nat_id {
+        what.to_string()
+    } else {
+        format!("{} ({})", what, name)
+    }
+}
+
+// The systems to mark on the map, by sys_id
+pub fn changed_systems(changes: &[Change]) -> HashSet<String> {
+    changes
+        .iter()
+        .filter(|c| !c.sys_id.is_empty())
+        .map(|c| c.sys_id.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Connection, Resource};
+
+    fn star(sys_id: &str, nat_id: &str, connections: &[&str]) -> Star {
+        Star {
+            sys_id: sys_id.to_string(),
+            name: nat_id.to_string(),
+            nat_id: nat_id.to_string(),
+            connections: connections.iter().map(|c| Connection { connection: c.to_string() }).collect(),
+            ..Star::new()
+        }
+    }
+
+    fn planet(sys_id: &str, nat_id: &str, name: &str) -> Planet {
+        Planet {
+            sys_id: sys_id.to_string(),
+            name: name.to_string(),
+            nat_id: nat_id.to_string(),
+            surface: true,
+            gravity: 1.0,
+            temp: 20.0,
+            pressure: 1.0,
+            fertility: -1.0,
+            orbit_index: None,
+            infrastructure: Vec::new(),
+            unknown: Default::default(),
+        }
+    }
+
+    fn resource(planet: &str, ticker: &str, factor: f64) -> Resource {
+        Resource {
+            planet: planet.to_string(),
+            ticker: ticker.to_string(),
+            typ: "MINERAL".to_string(),
+            factor,
+            unknown: Default::default(),
+        }
+    }
+
+    fn old() -> Dataset {
+        Dataset {
+            stars: vec![star("s1", "AA-001", &["s2"]), star("s2", "BB-002", &["s1"])],
+            planets: vec![
+                planet("s1", "AA-001a", "AA-001a"),
+                planet("s1", "AA-001b", "Montem"),
+                planet("s2", "BB-002a", "BB-002a"),
+            ],
+            resources: vec![resource("AA-001a", "FEO", 0.3), resource("AA-001a", "H2O", 0.1)],
+        }
+    }
+
+    fn messages(changes: &[Change], kind: ChangeKind) -> Vec<(&str, &str)> {
+        changes
+            .iter()
+            .filter(|c| c.kind == kind)
+            .map(|c| (c.id.as_str(), c.message.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn nothing_changed() {
+        assert!(diff(&old(), &old()).is_empty());
+    }
+
+    #[test]
+    fn systems_added_and_removed() {
+        let mut new = old();
+        new.stars.remove(1);
+        new.stars[0].connections.clear();
+        let mut added = star("s3", "CC-003", &[]);
+        added.name = "Hortus".to_string();
+        new.stars.push(added);
+        let changes = diff(&old(), &new);
+        assert_eq!(messages(&changes, ChangeKind::AddedSystem), [("CC-003", "added (Hortus)")]);
+        assert_eq!(messages(&changes, ChangeKind::RemovedSystem), [("BB-002", "removed")]);
+        assert_eq!(messages(&changes, ChangeKind::Connections), [("AA-001", "no longer connects to BB-002")]);
+    }
+
+    #[test]
+    fn connections_added_and_removed() {
+        let mut new = old();
+        new.stars.push(star("s3", "CC-003", &["s1"]));
+        new.stars[0].connections = vec![Connection { connection: "s3".to_string() }];
+        let changes = diff(&old(), &new);
+        assert_eq!(
+            messages(&changes, ChangeKind::Connections),
+            [("AA-001", "now connects to CC-003; no longer connects to BB-002")],
+        );
+    }
+
+    #[test]
+    fn planets_added_removed_and_renamed() {
+        let mut new = old();
+        new.planets[0].name = "Promitor".to_string();
+        new.planets[1].name = "Montem Prime".to_string();
+        new.planets.remove(2);
+        new.planets.push(planet("s2", "BB-002b", "BB-002b"));
+        let changes = diff(&old(), &new);
+        assert_eq!(messages(&changes, ChangeKind::AddedPlanet), [("BB-002b", "added")]);
+        assert_eq!(messages(&changes, ChangeKind::RemovedPlanet), [("BB-002a", "removed")]);
+        assert_eq!(messages(&changes, ChangeKind::RenamedPlanet), [
+            ("AA-001a", "named Promitor"),
+            ("AA-001b", "renamed from Montem to Montem Prime"),
+        ]);
+    }
+
+    #[test]
+    fn environments_changed() {
+        let mut new = old();
+        new.planets[0].surface = false;
+        new.planets[0].gravity = 1.25;
+        new.planets[2].fertility = 0.1;
+        let changes = diff(&old(), &new);
+        assert_eq!(messages(&changes, ChangeKind::Environment), [
+            ("AA-001a", "surface true to false, gravity 1.00 to 1.25"),
+            ("BB-002a", "fertility -1.00 to 0.10"),
+        ]);
+    }
+
+    #[test]
+    fn factors_changed_found_and_gone() {
+        let mut new = old();
+        new.resources = vec![
+            resource("AA-001a", "FEO", 0.35),
+            resource("AA-001b", "LST", 0.2),
+        ];
+        let changes = diff(&old(), &new);
+        assert_eq!(messages(&changes, ChangeKind::Factor), [
+            ("AA-001a", "FEO factor 0.3000 to 0.3500"),
+            ("AA-001a", "H2O gone, factor was 0.1000"),
+            ("AA-001b", "LST found, factor 0.2000"),
+        ]);
+        assert!(changes.iter().all(|c| c.sys_id == "s1"));
+    }
+
+    #[test]
+    fn changed_systems_skip_unknown_planets() {
+        let mut new = old();
+        new.stars.push(star("s3", "CC-003", &[]));
+        new.resources.push(resource("ZZ-999a", "FEO", 0.5));
+        new.planets[2].gravity = 2.0;
+        let changes = diff(&old(), &new);
+        let factor = changes.iter().find(|c| c.id == "ZZ-999a").unwrap();
+        assert_eq!(factor.sys_id, "");
+        let systems = changed_systems(&changes);
+        let expected: HashSet<String> = ["s2", "s3"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(systems, expected);
+    }
+}
@@ -1,16 +1,19 @@
+use web_sys::HtmlSelectElement;
 use yew::{html::IntoPropValue, prelude::*};
 
 use crate::models::*;
 
 #[derive(Clone, PartialEq, Properties)]
 pub struct Props {
-    pub env_filter: bool,
-    pub inc_normal: bool,
-    pub res_list: Vec<String>,
+    pub filters: Filters,
+    pub selected_res: Option<String>,
+    // By category, as (category, [(ticker, name)])
+    pub res_groups: Vec<(String, Vec<(String, String)>)>,
     pub toggle_signal: Callback<(Toggle, bool)>,
     pub surface_signal: Callback<SurfaceOption>,
     pub env_signal: Callback<(Environment, EnvironmentOption)>,
     pub selected_res_signal: Callback<Option<String>>,
+    pub reset_signal: Callback<()>,
 }
 
 pub enum Msg {
@@ -18,12 +21,20 @@ pub enum Msg {
     Surface(ChangeData),
     Environment(Environment, ChangeData),
     SelectedRes(ChangeData),
+    Reset,
 }
 
+// The selects are kept in step with Filters through their NodeRefs so that
+// state restored from a preset, a link or a reset shows the right option.
 #[allow(dead_code)]
 pub struct EditFilters {
     link: ComponentLink<Self>,
     props: Props,
+    surface_ref: NodeRef,
+    gravity_ref: NodeRef,
+    temp_ref: NodeRef,
+    pressure_ref: NodeRef,
+    res_ref: NodeRef,
 }
 
 impl Component for EditFilters {
@@ -34,6 +45,11 @@ impl Component for EditFilters {
         Self {
             link,
             props,
+            surface_ref: NodeRef::default(),
+            gravity_ref: NodeRef::default(),
+            temp_ref: NodeRef::default(),
+            pressure_ref: NodeRef::default(),
+            res_ref: NodeRef::default(),
         }
     }
 
@@ -50,10 +66,10 @@ impl Component for EditFilters {
         match msg {
             Msg::Toggle(toggle) => {
                 if let Toggle::IncEnvFilter = toggle {
-                    let v = !self.props.env_filter;
+                    let v = !self.props.filters.env_filter;
                     self.props.toggle_signal.emit((Toggle::IncEnvFilter, v));
                 } else {
-                    let v = !self.props.inc_normal;
+                    let v = !self.props.filters.inc_normal;
                     self.props.toggle_signal.emit((Toggle::IncNormal, v));
                 }
             }
@@ -78,10 +94,27 @@ impl Component for EditFilters {
                     self.props.selected_res_signal.emit(v);
                 }
             }
+            Msg::Reset => self.props.reset_signal.emit(()),
         }
         false
     }
 
+    fn rendered(&mut self, _first_render: bool) {
+        let filters = &self.props.filters;
+        set_select(&self.surface_ref, &filters.surface.to_string());
+        set_select(&self.gravity_ref, &filters.gravity.to_string());
+        set_select(&self.temp_ref, &filters.temp.to_string());
+        set_select(&self.pressure_ref, &filters.pressure.to_string());
+        match &self.props.selected_res {
+            Some(res) => set_select(&self.res_ref, res),
+            None => {
+                if let Some(select) = self.res_ref.cast::<HtmlSelectElement>() {
+                    select.set_selected_index(0);
+                }
+            }
+        }
+    }
+
     fn view(&self) -> Html {
         let gravity_callback =
             |cd| Msg::Environment(Environment::Gravity, cd);
@@ -92,39 +125,44 @@ impl Component for EditFilters {
         let env_filter_callback = |_| Msg::Toggle(Toggle::IncEnvFilter);
         let inc_normal_callback = |_| Msg::Toggle(Toggle::IncNormal);
 
-        let rocky = SurfaceOption::Rocky.to_string();
-        let gaseous = SurfaceOption::Gaseous.to_string();
-        let both = SurfaceOption::Both.to_string();
+        let filters = &self.props.filters;
+        let surface = &filters.surface;
+        let selected_res = &self.props.selected_res;
 
         html! {
             <div>
                 {checkbox("Include Environment Filter?".to_string(),
-                    self.props.env_filter, self.link.callback(env_filter_callback))
+                    filters.env_filter, self.link.callback(env_filter_callback))
                 }
-                <div class="environment-filter" hidden={!self.props.env_filter}>
+                <div class="environment-filter" hidden={!filters.env_filter}>
                 {"Environment Filter"}
                     <div>
                         <select
+                            ref=self.surface_ref.clone()
                             onchange=self.link.callback(Msg::Surface)
                         >
-                            <option value={rocky.clone()}>{rocky}</option>
-                            <option value={gaseous.clone()}>{gaseous}</option>
-                            <option value={both.clone()}>{both}</option>
+                        { for [SurfaceOption::Rocky, SurfaceOption::Gaseous, SurfaceOption::Both]
+                            .iter()
+                            .map(|o| {
+                                let v = o.to_string();
+                                html! { <option value={v.clone()} selected={o == surface}>{v}</option> }
+                            })
+                        }
                         </select>
                         {"Surface"}
                     </div>
-                    {dropdown(Position::R, "Gravity".to_string(),
-                        self.link.callback(gravity_callback))
+                    {dropdown(Position::R, "Gravity".to_string(), &filters.gravity,
+                        &self.gravity_ref, self.link.callback(gravity_callback))
                     }
-                    {dropdown(Position::R, "Temperature".to_string(),
-                        self.link.callback(temp_callback))
+                    {dropdown(Position::R, "Temperature".to_string(), &filters.temp,
+                        &self.temp_ref, self.link.callback(temp_callback))
                     }
-                    {dropdown(Position::R, "Pressure".to_string(),
-                        self.link.callback(pressure_callback))
+                    {dropdown(Position::R, "Pressure".to_string(), &filters.pressure,
+                        &self.pressure_ref, self.link.callback(pressure_callback))
                     }
                     <div class="inc-normal">
                     {checkbox("Include Normal with Low and High?".to_string(),
-                        self.props.inc_normal, self.link.callback(inc_normal_callback))
+                        filters.inc_normal, self.link.callback(inc_normal_callback))
                     }
                     </div>
                 </div>
@@ -132,14 +170,28 @@ impl Component for EditFilters {
                     <div>
                     {"Include Resource Filter:"}
                         <select
+                            ref=self.res_ref.clone()
                             onchange=self.link.callback(Msg::SelectedRes)
                         >
-                        { for self.props.res_list.iter().map(|k| {
-                            html! { <option value={k.to_string()}>{k}</option> }
+                        <option value="-None-" selected=selected_res.is_none()>{"-None-"}</option>
+                        { for self.props.res_groups.iter().map(|(category, tickers)| html! {
+                            <optgroup label=category.clone()>
+                            { for tickers.iter().map(|(ticker, name)| {
+                                let selected = selected_res.as_ref() == Some(ticker);
+                                html! {
+                                    <option value=ticker.clone() title=name.clone() selected=selected>
+                                        {format!("{} - {}", ticker, name)}
+                                    </option>
+                                }
+                            })}
+                            </optgroup>
                         })}
                         </select>
                     </div>
                 </div>
+                <div class="reset-filters">
+                    <button onclick=self.link.callback(|_| Msg::Reset)>{"Reset filters"}</button>
+                </div>
             </div>
         }
     }
@@ -159,25 +211,49 @@ where Callback<T>: IntoPropValue<Option<Callback<ChangeData>>> {
     }
 }
 
-fn dropdown<T>(pos: Position, label: String, cb: Callback<T>) -> Html
+fn dropdown<T>(
+    pos: Position,
+    label: String,
+    selected: &EnvironmentOption,
+    node_ref: &NodeRef,
+    cb: Callback<T>,
+) -> Html
 where Callback<T>: IntoPropValue<Option<Callback<ChangeData>>> {
-    let normal = EnvironmentOption::Normal.to_string();
-    let low = EnvironmentOption::Low.to_string();
-    let high = EnvironmentOption::High.to_string();
-    let ignore = EnvironmentOption::Ignore.to_string();
+    let options = [
+        EnvironmentOption::Normal,
+        EnvironmentOption::Low,
+        EnvironmentOption::High,
+        EnvironmentOption::Ignore,
+    ];
     let none = "".to_string();
+    // A range only comes from a query, so it's offered only while it's set
+    let range = match selected {
+        EnvironmentOption::Range(..) => {
+            let v = selected.to_string();
+            html! { <option value={v.clone()} selected=true>{format!("Range {}", v)}</option> }
+        }
+        _ => html! {},
+    };
     html! {
         <div>
             { if let Position::L = pos {&label} else {&none} }
             <select
+                ref=node_ref.clone()
                 onchange={cb}
             >
-                <option value={normal.clone()}>{normal}</option>
-                <option value={low.clone()}>{low}</option>
-                <option value={high.clone()}>{high}</option>
-                <option value={ignore.clone()}>{ignore}</option>
+            { for options.iter().map(|o| {
+                let v = o.to_string();
+                html! { <option value={v.clone()} selected={o == selected}>{v}</option> }
+            })}
+            {range}
             </select>
             { if let Position::R = pos {label} else {none} }
         </div>
     }
 }
+
+fn set_select(node_ref: &NodeRef, value: &str) {
+    if let Some(select) = node_ref.cast::<HtmlSelectElement>() {
+        select.set_value(value);
+    }
+}
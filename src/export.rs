@@ -0,0 +1,212 @@
+// Filter results as CSV or JSON, one row per planet and resource, for
+// spreadsheets and other tools.
+use serde_derive::Serialize;
+use std::collections::HashMap;
+
+use crate::models::{FilterResults, Planet, Resource, Star, Universe};
+
+#[derive(Serialize, PartialEq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportRow {
+    pub planet: String,
+    pub planet_name: String,
+    pub system: String,
+    pub system_name: String,
+    pub surface: String,
+    pub gravity: f64,
+    pub temp: f64,
+    pub pressure: f64,
+    pub fertility: f64,
+    pub ticker: Option<String>,
+    #[serde(rename = "type")]
+    pub typ: Option<String>,
+    pub factor: Option<f64>,
+    pub percent_of_max: Option<f64>,
+}
+
+const CSV_HEADER: [&str; 13] = [
+    "planet", "planetName", "system", "systemName", "surface", "gravity",
+    "temp", "pressure", "fertility", "ticker", "type", "factor", "percentOfMax",
+];
+
+impl ExportRow {
+    fn new(universe: &Universe, star: Option<&Star>, p: &Planet, r: Option<&Resource>) -> Self {
+        let percent_of_max = r.and_then(|r| {
+            universe.res_max_factor
+                .get(&r.ticker)
+                .filter(|&&max| max > 0.0)
+                .map(|max| r.factor / max * 100.0)
+        });
+        Self {
+            planet: p.nat_id.clone(),
+            planet_name: p.name.clone(),
+            system: star.map(|s| s.nat_id.clone()).unwrap_or_default(),
+            system_name: star.map(|s| s.name.clone()).unwrap_or_default(),
+            surface: if p.surface { "Rocky" } else { "Gaseous" }.to_string(),
+            gravity: p.gravity,
+            temp: p.temp,
+            pressure: p.pressure,
+            fertility: p.fertility,
+            ticker: r.map(|r| r.ticker.clone()),
+            typ: r.map(|r| r.typ.clone()),
+            factor: r.map(|r| r.factor),
+            percent_of_max,
+        }
+    }
+    fn csv_fields(&self) -> Vec<String> {
+        let opt = |v: &Option<f64>| v.map(|v| v.to_string()).unwrap_or_default();
+        vec![
+            self.planet.clone(),
+            self.planet_name.clone(),
+            self.system.clone(),
+            self.system_name.clone(),
+            self.surface.clone(),
+            self.gravity.to_string(),
+            self.temp.to_string(),
+            self.pressure.to_string(),
+            self.fertility.to_string(),
+            self.ticker.clone().unwrap_or_default(),
+            self.typ.clone().unwrap_or_default(),
+            opt(&self.factor),
+            opt(&self.percent_of_max),
+        ]
+    }
+}
+
+fn stars_by_id(universe: &Universe) -> HashMap<&str, &Star> {
+    universe.stars.iter().map(|s| (s.sys_id.as_str(), s)).collect()
+}
+
+// The planet and resource pairs in Diagnostics::filter_hits
+pub fn hit_rows(universe: &Universe, results: &FilterResults) -> Vec<ExportRow> {
+    let stars = stars_by_id(universe);
+    universe.hits(results)
+        .map(|(p, r)| {
+            let star = stars.get(p.sys_id.as_str()).copied();
+            ExportRow::new(universe, star, p, Some(r))
+        })
+        .collect()
+}
+
+// Every planet passing the filters with each of its resources. Planets with
+// no resources still get a row.
+pub fn filtered_planet_rows(universe: &Universe, results: &FilterResults) -> Vec<ExportRow> {
+    planet_rows(universe, |p| results.planets.contains(&p.nat_id))
+}
+
+// As filtered_planet_rows, for the planets the test picks
+pub fn planet_rows<F: Fn(&Planet) -> bool>(universe: &Universe, test: F) -> Vec<ExportRow> {
+    let stars = stars_by_id(universe);
+    let mut resources: HashMap<&str, Vec<&Resource>> = HashMap::new();
+    for r in &universe.resources {
+        resources.entry(r.planet.as_str()).or_default().push(r);
+    }
+    let mut rows = Vec::new();
+    for p in universe.planets.iter().filter(|p| test(p)) {
+        let star = stars.get(p.sys_id.as_str()).copied();
+        match resources.get(p.nat_id.as_str()) {
+            Some(rs) => {
+                for r in rs {
+                    rows.push(ExportRow::new(universe, star, p, Some(r)));
+                }
+            }
+            None => rows.push(ExportRow::new(universe, star, p, None)),
+        }
+    }
+    rows
+}
+
+pub fn to_csv(rows: &[ExportRow]) -> String {
+    let mut csv = CSV_HEADER.join(",");
+    csv.push('\n');
+    for row in rows {
+        let fields: Vec<String> = row.csv_fields().iter().map(|f| csv_field(f)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+pub fn to_json(rows: &[ExportRow]) -> String {
+    serde_json::to_string_pretty(rows).expect("export rows serialize")
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn row() -> ExportRow {
+        ExportRow {
+            planet: "ZV-639a".to_string(),
+            planet_name: "Montem, \"the Wet\"".to_string(),
+            system: "ZV-639".to_string(),
+            system_name: "Hortus\nMajor".to_string(),
+            surface: "Rocky".to_string(),
+            gravity: 0.98,
+            temp: 22.5,
+            pressure: 1.0,
+            fertility: -1.0,
+            ticker: Some("FEO".to_string()),
+            typ: Some("MINERAL".to_string()),
+            factor: Some(0.25),
+            percent_of_max: None,
+        }
+    }
+
+    #[test]
+    fn plain_fields_are_left_alone() {
+        assert_eq!(csv_field("ZV-639a"), "ZV-639a");
+        assert_eq!(csv_field(""), "");
+    }
+
+    #[test]
+    fn awkward_fields_are_quoted() {
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(csv_field("two\r\nlines"), "\"two\r\nlines\"");
+    }
+
+    #[test]
+    fn csv_has_a_header_and_a_line_per_row() {
+        let csv = to_csv(&[row()]);
+        assert_eq!(csv, concat!(
+            "planet,planetName,system,systemName,surface,gravity,temp,pressure,fertility,",
+            "ticker,type,factor,percentOfMax\n",
+            "ZV-639a,\"Montem, \"\"the Wet\"\"\",ZV-639,\"Hortus\nMajor\",Rocky,0.98,22.5,1,-1,",
+            "FEO,MINERAL,0.25,\n",
+        ));
+        assert_eq!(to_csv(&[]), format!("{}\n", CSV_HEADER.join(",")));
+    }
+
+    #[test]
+    fn header_lines_up_with_the_fields() {
+        // Each column holds the JSON export's field of the same name
+        let row = row();
+        let json = serde_json::to_value(&row).unwrap();
+        let fields = row.csv_fields();
+        assert_eq!(fields.len(), CSV_HEADER.len());
+        assert_eq!(json.as_object().unwrap().len(), CSV_HEADER.len());
+        for (name, field) in CSV_HEADER.iter().zip(&fields) {
+            let expected = match &json[name] {
+                Value::String(s) => s.clone(),
+                Value::Null => String::new(),
+                other => other.to_string(),
+            };
+            let parsed = |s: &str| s.parse::<f64>().ok();
+            match (parsed(field), parsed(&expected)) {
+                (Some(a), Some(b)) => assert_eq!(a, b, "{}", name),
+                _ => assert_eq!(field, &expected, "{}", name),
+            }
+        }
+    }
+}
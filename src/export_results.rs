@@ -0,0 +1,97 @@
+use yew::prelude::*;
+use yew::services::ConsoleService;
+
+use crate::download::download;
+use crate::export::{filtered_planet_rows, hit_rows, to_csv, to_json, ExportRow};
+use crate::models::{Universe, View};
+use crate::shared::Shared;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    pub universe: Shared<Universe>,
+    pub view: View,
+}
+
+#[derive(Clone, Copy)]
+pub enum Set {
+    Hits,
+    Planets,
+}
+
+#[derive(Clone, Copy)]
+pub enum Format {
+    Csv,
+    Json,
+}
+
+pub enum Msg {
+    Export(Set, Format),
+}
+
+#[allow(dead_code)]
+pub struct ExportResults {
+    link: ComponentLink<Self>,
+    props: Props,
+}
+
+impl Component for ExportResults {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Self { link, props }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> bool {
+        if self.props != props {
+            self.props = props;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Export(set, format) => {
+                let (rows, name): (Vec<ExportRow>, _) = match set {
+                    Set::Hits => (hit_rows(&self.props.universe, &self.props.view.results), "filter-hits"),
+                    Set::Planets => (filtered_planet_rows(&self.props.universe, &self.props.view.results), "filtered-planets"),
+                };
+                let result = match format {
+                    Format::Csv => download(&format!("{}.csv", name), "text/csv", &to_csv(&rows)),
+                    Format::Json => {
+                        download(&format!("{}.json", name), "application/json", &to_json(&rows))
+                    }
+                };
+                if result.is_err() {
+                    ConsoleService::error("Unable to download the export");
+                }
+            }
+        }
+        false
+    }
+
+    fn view(&self) -> Html {
+        let no_hits = self.props.view.results.diagnostics.filter_hits.is_empty();
+        let button = |set: Set, format: Format, label: &str, disabled: bool| {
+            html! {
+                <button
+                    disabled=disabled
+                    onclick=self.link.callback(move |_| Msg::Export(set, format))
+                >
+                    {label}
+                </button>
+            }
+        };
+        html! {
+            <div class="export-results">
+                {"Export"}
+                {button(Set::Hits, Format::Csv, "Hits CSV", no_hits)}
+                {button(Set::Hits, Format::Json, "Hits JSON", no_hits)}
+                {button(Set::Planets, Format::Csv, "Planets CSV", false)}
+                {button(Set::Planets, Format::Json, "Planets JSON", false)}
+            </div>
+        }
+    }
+}
@@ -0,0 +1,113 @@
+// Daily output of a resource from the extractor building that can work it.
+use serde_derive::{Deserialize, Serialize};
+
+use crate::models::Resource;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Extractor {
+    Extractor,
+    Collector,
+    Rig,
+}
+
+impl Extractor {
+    pub fn for_type(typ: &str) -> Option<Self> {
+        match typ.to_ascii_uppercase().as_str() {
+            "MINERAL" => Some(Extractor::Extractor),
+            "GASEOUS" => Some(Extractor::Collector),
+            "LIQUID" => Some(Extractor::Rig),
+            _ => None,
+        }
+    }
+    pub fn ticker(&self) -> &'static str {
+        match self {
+            Extractor::Extractor => "EXT",
+            Extractor::Collector => "COL",
+            Extractor::Rig => "RIG",
+        }
+    }
+    // Units a day from one building at full efficiency on a factor of 1.0.
+    // As in the game's recipes: the extractor and rig share a rate, only
+    // the collector is slower.
+    pub fn base_rate(&self) -> f64 {
+        match self {
+            Extractor::Extractor | Extractor::Rig => 70.0,
+            Extractor::Collector => 60.0,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
+pub struct ExtractionSettings {
+    pub buildings: u32,
+    // 1.0 is 100%
+    pub efficiency: f64,
+}
+
+impl ExtractionSettings {
+    pub fn new() -> Self {
+        Self {
+            buildings: 1,
+            efficiency: 1.0,
+        }
+    }
+}
+
+impl Default for ExtractionSettings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn daily_output(res: &Resource, settings: &ExtractionSettings) -> f64 {
+    match Extractor::for_type(&res.typ) {
+        Some(e) => e.base_rate() * res.factor * settings.buildings as f64 * settings.efficiency,
+        None => 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resource(typ: &str, factor: f64) -> Resource {
+        Resource {
+            planet: "ZV-639a".to_string(),
+            ticker: "FEO".to_string(),
+            typ: typ.to_string(),
+            factor,
+            unknown: Default::default(),
+        }
+    }
+
+    fn output(typ: &str, factor: f64, buildings: u32, efficiency: f64) -> f64 {
+        daily_output(&resource(typ, factor), &ExtractionSettings { buildings, efficiency })
+    }
+
+    #[test]
+    fn each_extractor_has_its_rate() {
+        assert_eq!(output("MINERAL", 1.0, 1, 1.0), 70.0);
+        assert_eq!(output("LIQUID", 1.0, 1, 1.0), 70.0);
+        assert_eq!(output("GASEOUS", 1.0, 1, 1.0), 60.0);
+        assert_eq!(output("mineral", 1.0, 1, 1.0), 70.0);
+        assert_eq!(output("PLASMA", 1.0, 1, 1.0), 0.0);
+    }
+
+    #[test]
+    fn output_scales_with_factor_buildings_and_efficiency() {
+        assert!((output("MINERAL", 0.25, 1, 1.0) - 17.5).abs() < 1e-9);
+        assert!((output("GASEOUS", 0.5, 3, 1.0) - 90.0).abs() < 1e-9);
+        assert!((output("LIQUID", 0.5, 2, 0.8) - 56.0).abs() < 1e-9);
+        assert_eq!(output("MINERAL", 0.5, 0, 1.0), 0.0);
+    }
+
+    #[test]
+    fn types_have_their_buildings() {
+        let tickers: Vec<&str> = ["MINERAL", "GASEOUS", "LIQUID"]
+            .iter()
+            .filter_map(|t| Extractor::for_type(t))
+            .map(|e| e.ticker())
+            .collect();
+        assert_eq!(tickers, ["EXT", "COL", "RIG"]);
+    }
+}
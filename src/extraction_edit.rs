@@ -0,0 +1,85 @@
+use yew::prelude::*;
+
+use crate::extraction::ExtractionSettings;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    pub settings: ExtractionSettings,
+    pub settings_signal: Callback<ExtractionSettings>,
+}
+
+pub enum Msg {
+    Buildings(ChangeData),
+    Efficiency(ChangeData),
+}
+
+#[allow(dead_code)]
+pub struct ExtractionEdit {
+    link: ComponentLink<Self>,
+    props: Props,
+}
+
+impl Component for ExtractionEdit {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Self { link, props }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> bool {
+        if self.props != props {
+            self.props = props;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        let mut settings = self.props.settings;
+        match msg {
+            Msg::Buildings(ChangeData::Value(v)) => {
+                if let Ok(n) = v.parse::<u32>() {
+                    settings.buildings = n.max(1);
+                }
+            }
+            Msg::Efficiency(ChangeData::Value(v)) => {
+                if let Ok(pct) = v.parse::<f64>() {
+                    settings.efficiency = pct.max(0.0) / 100.0;
+                }
+            }
+            _ => (),
+        }
+        self.props.settings_signal.emit(settings);
+        // Redraw to put back any value that didn't parse
+        true
+    }
+
+    fn view(&self) -> Html {
+        let settings = &self.props.settings;
+        html! {
+            <div class="extraction-edit">
+                {"Extraction per day:"}
+                <div>
+                    <input
+                        type="number"
+                        min="1"
+                        value=settings.buildings.to_string()
+                        onchange=self.link.callback(Msg::Buildings)
+                    />
+                    {"Buildings"}
+                </div>
+                <div>
+                    <input
+                        type="number"
+                        min="0"
+                        value=format!("{:.0}", settings.efficiency * 100.0)
+                        onchange=self.link.callback(Msg::Efficiency)
+                    />
+                    {"% Efficiency"}
+                </div>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use yew::format::Json;
+use yew::services::storage::{Area, StorageService};
+
+const FAVOURITES_KEY: &str = "prun.favourites";
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum FavouriteKind {
+    Planet,
+    System,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct Favourite {
+    pub kind: FavouriteKind,
+    pub name: String,
+    // nat_id of the system, itself for a system
+    pub system: String,
+    #[serde(default)]
+    pub note: String,
+}
+
+// By planet or system nat_id
+pub type Favourites = BTreeMap<String, Favourite>;
+
+pub enum FavouriteAction {
+    Toggle(FavouriteKind, String),
+    Note(String, String),
+    Import(Favourites),
+}
+
+// Favourites live in localStorage next to the presets, and like them are
+// kept in memory only when storage isn't available.
+pub struct FavouriteStore {
+    storage: Option<StorageService>,
+    favourites: Favourites,
+}
+
+impl FavouriteStore {
+    pub fn new() -> Self {
+        let storage = StorageService::new(Area::Local).ok();
+        let favourites = match &storage {
+            Some(s) => {
+                let Json(favourites) = s.restore(FAVOURITES_KEY);
+                favourites.unwrap_or_default()
+            }
+            None => BTreeMap::new(),
+        };
+        Self { storage, favourites }
+    }
+    pub fn all(&self) -> &Favourites {
+        &self.favourites
+    }
+    // Adds the favourite, or removes it (and its note) if already there
+    pub fn toggle(&mut self, nat_id: String, favourite: Favourite) {
+        if self.favourites.remove(&nat_id).is_none() {
+            self.favourites.insert(nat_id, favourite);
+        }
+        self.store();
+    }
+    pub fn set_note(&mut self, nat_id: &str, note: String) {
+        if let Some(favourite) = self.favourites.get_mut(nat_id) {
+            favourite.note = note;
+            self.store();
+        }
+    }
+    // Imported favourites replace any with the same nat_id
+    pub fn import(&mut self, favourites: Favourites) {
+        self.favourites.extend(favourites);
+        self.store();
+    }
+    fn store(&mut self) {
+        if let Some(storage) = &mut self.storage {
+            storage.store(FAVOURITES_KEY, Json(&self.favourites));
+        }
+    }
+}
+
+impl Default for FavouriteStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
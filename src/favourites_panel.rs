@@ -0,0 +1,168 @@
+use yew::prelude::*;
+use yew::services::reader::{FileData, ReaderService, ReaderTask};
+use yew::services::ConsoleService;
+
+use crate::download::download;
+use crate::favourites::{FavouriteAction, FavouriteKind, Favourites};
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    pub favourites: Favourites,
+    pub favourite_signal: Callback<FavouriteAction>,
+    pub search_star_signal: Callback<String>,
+}
+
+pub enum Msg {
+    Select(String),
+    Remove(String),
+    Note(String, String),
+    Export,
+    ImportFile(ChangeData),
+    Imported(FileData),
+}
+
+#[allow(dead_code)]
+pub struct FavouritesPanel {
+    link: ComponentLink<Self>,
+    props: Props,
+    reader_task: Option<ReaderTask>,
+    import_error: Option<String>,
+}
+
+impl Component for FavouritesPanel {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Self {
+            link,
+            props,
+            reader_task: None,
+            import_error: None,
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> bool {
+        if self.props != props {
+            self.props = props;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Select(nat_id) => {
+                self.props.search_star_signal.emit(nat_id);
+                false
+            }
+            Msg::Remove(nat_id) => {
+                if let Some(f) = self.props.favourites.get(&nat_id) {
+                    self.props.favourite_signal.emit(FavouriteAction::Toggle(f.kind, nat_id));
+                }
+                false
+            }
+            Msg::Note(nat_id, note) => {
+                self.props.favourite_signal.emit(FavouriteAction::Note(nat_id, note));
+                false
+            }
+            Msg::Export => {
+                let json = serde_json::to_string_pretty(&self.props.favourites)
+                    .expect("favourites serialize");
+                if download("favourites.json", "application/json", &json).is_err() {
+                    ConsoleService::error("Unable to download the favourites");
+                }
+                false
+            }
+            Msg::ImportFile(ChangeData::Files(files)) => {
+                if let Some(file) = files.get(0) {
+                    let callback = self.link.callback(Msg::Imported);
+                    self.reader_task = ReaderService::read_file(file, callback).ok();
+                }
+                false
+            }
+            Msg::ImportFile(_) => false,
+            Msg::Imported(data) => {
+                self.reader_task = None;
+                match serde_json::from_slice::<Favourites>(&data.content) {
+                    Ok(favourites) => {
+                        self.import_error = None;
+                        self.props.favourite_signal.emit(FavouriteAction::Import(favourites));
+                    }
+                    Err(e) => self.import_error = Some(format!("{}: {}", data.name, e)),
+                }
+                true
+            }
+        }
+    }
+
+    fn view(&self) -> Html {
+        html! {
+            <div class="favourites">
+                <h3>{"Favourites"}</h3>
+                { if self.props.favourites.is_empty() {
+                    html! { <div>{"Star a planet or system to add it here"}</div> }
+                } else {
+                    html! {}
+                }}
+                <ul>
+                { for self.props.favourites.iter().map(|(nat_id, f)| {
+                    let select = nat_id.clone();
+                    let remove = nat_id.clone();
+                    let note = nat_id.clone();
+                    let icon = match f.kind {
+                        FavouriteKind::Planet => "fas fa-globe",
+                        FavouriteKind::System => "fas fa-sun",
+                    };
+                    html! {
+                        <li>
+                            <i class=icon />
+                            <span
+                                class="link"
+                                onclick=self.link.callback(move |_| Msg::Select(select.clone()))
+                            >
+                                {&f.name}
+                            </span>
+                            <i
+                                class="fas fa-times unfavourite"
+                                onclick=self.link.callback(move |_| Msg::Remove(remove.clone()))
+                            />
+                            <input
+                                type="text"
+                                class="favourite-note"
+                                placeholder="Note"
+                                value=f.note.clone()
+                                onchange=self.link.callback(move |e: ChangeData| match e {
+                                    ChangeData::Value(v) => Msg::Note(note.clone(), v),
+                                    _ => Msg::Note(note.clone(), String::new()),
+                                })
+                            />
+                        </li>
+                    }
+                })}
+                </ul>
+                <div class="favourites-io">
+                    <button
+                        disabled=self.props.favourites.is_empty()
+                        onclick=self.link.callback(|_| Msg::Export)
+                    >
+                        {"Export JSON"}
+                    </button>
+                    <label class="import">
+                        {"Import JSON"}
+                        <input
+                            type="file"
+                            accept=".json,application/json"
+                            onchange=self.link.callback(Msg::ImportFile)
+                        />
+                    </label>
+                </div>
+                { match &self.import_error {
+                    Some(e) => html! { <div class="error">{e}</div> },
+                    None => html! {},
+                }}
+            </div>
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use yew::prelude::*;
+use yew::services::DialogService;
+
+use crate::presets::PresetAction;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    pub presets: Vec<String>,
+    pub preset_signal: Callback<PresetAction>,
+}
+
+pub enum Msg {
+    Name(String),
+    Selected(ChangeData),
+    Save,
+    Load,
+    Rename,
+    Delete,
+}
+
+#[allow(dead_code)]
+pub struct FilterPresets {
+    link: ComponentLink<Self>,
+    props: Props,
+    name: String,
+    selected: Option<String>,
+}
+
+impl FilterPresets {
+    // Saving or renaming over another preset only once the user agrees
+    fn may_replace(&self, name: &str) -> bool {
+        !self.props.presets.iter().any(|p| p == name)
+            || DialogService::confirm(&format!("Replace the preset \"{}\"?", name))
+    }
+}
+
+impl Component for FilterPresets {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let selected = props.presets.first().cloned();
+        Self {
+            link,
+            props,
+            name: String::new(),
+            selected,
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> bool {
+        if self.props != props {
+            self.props = props;
+            let still_there = match &self.selected {
+                Some(s) => self.props.presets.contains(s),
+                None => false,
+            };
+            if !still_there {
+                self.selected = self.props.presets.first().cloned();
+            }
+            true
+        } else {
+            false
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Name(name) => {
+                self.name = name;
+                false
+            }
+            Msg::Selected(cd) => {
+                if let ChangeData::Select(select) = cd {
+                    self.selected = Some(select.value());
+                }
+                false
+            }
+            Msg::Save => {
+                let name = self.name.trim().to_string();
+                if !name.is_empty() && self.may_replace(&name) {
+                    self.selected = Some(name.clone());
+                    self.props.preset_signal.emit(PresetAction::Save(name));
+                    self.name = String::new();
+                }
+                true
+            }
+            Msg::Load => {
+                if let Some(name) = &self.selected {
+                    self.props.preset_signal.emit(PresetAction::Load(name.clone()));
+                }
+                false
+            }
+            Msg::Rename => {
+                let name = self.name.trim().to_string();
+                if let Some(from) = self.selected.take() {
+                    if !name.is_empty() && (name == from || self.may_replace(&name)) {
+                        self.selected = Some(name.clone());
+                        self.props.preset_signal.emit(PresetAction::Rename(from, name));
+                        self.name = String::new();
+                    } else {
+                        self.selected = Some(from);
+                    }
+                }
+                true
+            }
+            Msg::Delete => {
+                if let Some(name) = self.selected.take() {
+                    self.props.preset_signal.emit(PresetAction::Delete(name));
+                }
+                true
+            }
+        }
+    }
+
+    fn view(&self) -> Html {
+        let no_presets = self.props.presets.is_empty();
+        html! {
+            <div class="filter-presets">
+                {"Filter Presets"}
+                <div>
+                    <select
+                        disabled=no_presets
+                        onchange=self.link.callback(Msg::Selected)
+                    >
+                    { for self.props.presets.iter().map(|k| {
+                        let selected = self.selected.as_ref() == Some(k);
+                        html! { <option value={k.to_string()} selected={selected}>{k}</option> }
+                    })}
+                    </select>
+                    <button disabled=no_presets onclick=self.link.callback(|_| Msg::Load)>
+                        {"Load"}
+                    </button>
+                    <button disabled=no_presets onclick=self.link.callback(|_| Msg::Delete)>
+                        {"Delete"}
+                    </button>
+                </div>
+                <div>
+                    <input
+                        class="preset-name"
+                        placeholder="Preset name"
+                        value=self.name.clone()
+                        oninput=self.link.callback(|e: InputData| Msg::Name(e.value))
+                    />
+                    <button onclick=self.link.callback(|_| Msg::Save)>{"Save"}</button>
+                    <button disabled=no_presets onclick=self.link.callback(|_| Msg::Rename)>
+                        {"Rename"}
+                    </button>
+                </div>
+            </div>
+        }
+    }
+}
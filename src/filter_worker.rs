@@ -0,0 +1,203 @@
+// Parsing, checking and indexing the full universe, and filtering it, take
+// long enough to stall the page, so they're done in a web worker. The app
+// passes on each data file as it arrives and the worker, once it has them
+// all, builds the universe and sends it back: the cleaned records packed
+// (see pack.rs) along with everything worked out from them. The app then
+// asks for FilterResults whenever the filters change, whose indexes are
+// into those same records. Snapshots are compared with the data as it was
+// loaded, before any records were dropped. The worker is the prun-worker
+// bin.
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+use yew::agent::{Agent, AgentLink, HandlerId, Public};
+
+use crate::dataset::{Dataset, PLANETS_FILE, RESOURCES_FILE, STARS_FILE};
+use crate::diff::{diff, Change};
+use crate::models::{CxDistance, FilterResults, Filters, Universe, UniverseIndex};
+use crate::pack::{pack, unpack, PACK_FILE};
+use crate::validate::Issue;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub enum Request {
+    // A data file as fetched, by file name
+    Load(String, Vec<u8>),
+    // Numbered so the app can tell the answer to its latest request
+    Filter(u32, Filters, Option<String>),
+    // A packed snapshot to compare with, by file name
+    Diff(String, Vec<u8>),
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub enum Response {
+    Built(Built),
+    // A data file that couldn't be read, and why
+    Failed(String, String),
+    Filtered(u32, FilterResults),
+    // The snapshot's name and its changes to the data, or why there are none
+    Diffed(String, Result<Vec<Change>, String>),
+}
+
+// The universe as the worker has it once it's checked and indexed
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Built {
+    // The cleaned records, in the worker's order
+    pub packed: Vec<u8>,
+    pub index: UniverseIndex,
+    pub cx_distances: HashMap<String, CxDistance>,
+    pub data_issues: Vec<Issue>,
+}
+
+pub struct FilterWorker {
+    link: AgentLink<Self>,
+    universe: Universe,
+    // The records as they were read
+    loaded: Dataset,
+    built: bool,
+    // A filter asked for before the data was in
+    waiting: Option<(HandlerId, u32, Filters, Option<String>)>,
+}
+
+impl FilterWorker {
+    fn load(&mut self, file: &str, bytes: &[u8]) -> anyhow::Result<()> {
+        let universe = &mut self.universe;
+        match file {
+            PACK_FILE => {
+                let data = unpack(bytes)?;
+                universe.stars = data.stars;
+                universe.planets = data.planets;
+                universe.resources = data.resources;
+            }
+            STARS_FILE => universe.stars = serde_json::from_slice(bytes)?,
+            PLANETS_FILE => universe.planets = serde_json::from_slice(bytes)?,
+            RESOURCES_FILE => universe.resources = serde_json::from_slice(bytes)?,
+            _ => anyhow::bail!("not a data file"),
+        }
+        Ok(())
+    }
+    fn filter(&self, id: HandlerId, seq: u32, filters: &Filters, selected_res: &Option<String>) {
+        let results = self.universe.filter(filters, selected_res);
+        self.link.respond(id, Response::Filtered(seq, results));
+    }
+}
+
+fn build(universe: &mut Universe) -> anyhow::Result<Built> {
+    universe.fix_star_y();
+    universe.build();
+    Ok(Built {
+        packed: pack(&universe.dataset())?,
+        index: universe.index(),
+        cx_distances: universe.cx_distances.clone(),
+        data_issues: universe.data_issues.clone(),
+    })
+}
+
+impl Agent for FilterWorker {
+    type Reach = Public<Self>;
+    type Message = ();
+    type Input = Request;
+    type Output = Response;
+
+    fn create(link: AgentLink<Self>) -> Self {
+        Self {
+            link,
+            universe: Universe::new(),
+            loaded: Dataset::default(),
+            built: false,
+            waiting: None,
+        }
+    }
+
+    fn update(&mut self, _msg: Self::Message) {}
+
+    fn handle_input(&mut self, msg: Self::Input, id: HandlerId) {
+        match msg {
+            Request::Load(file, bytes) => {
+                if let Err(e) = self.load(&file, &bytes) {
+                    self.link.respond(id, Response::Failed(file, format!("{:#}", e)));
+                    return;
+                }
+                let universe = &self.universe;
+                if self.built
+                    || universe.stars.is_empty()
+                    || universe.planets.is_empty()
+                    || universe.resources.is_empty() {
+                    return;
+                }
+                self.built = true;
+                self.loaded = self.universe.dataset();
+                match build(&mut self.universe) {
+                    Ok(built) => self.link.respond(id, Response::Built(built)),
+                    Err(e) => self.link.respond(id, Response::Failed("universe".to_string(), format!("{:#}", e))),
+                }
+                if let Some((id, seq, filters, selected_res)) = self.waiting.take() {
+                    self.filter(id, seq, &filters, &selected_res);
+                }
+            }
+            Request::Filter(seq, filters, selected_res) => {
+                if self.built {
+                    self.filter(id, seq, &filters, &selected_res);
+                } else {
+                    self.waiting = Some((id, seq, filters, selected_res));
+                }
+            }
+            Request::Diff(name, bytes) => {
+                let changes = if self.built {
+                    unpack(&bytes)
+                        .map(|snapshot| diff(&snapshot, &self.loaded))
+                        .map_err(|e| format!("{:#}", e))
+                } else {
+                    Err("the data hasn't loaded yet".to_string())
+                };
+                self.link.respond(id, Response::Diffed(name, changes));
+            }
+        }
+    }
+
+    fn name_of_resource() -> &'static str {
+        "prun-worker.js"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STARS: &str = r#"[
+        {"sysId": "s1", "name": "Antares I", "natId": "ZV-307", "type": "M",
+         "x": 0.0, "y": 1.0, "z": 0.0, "connections": [{"connection": "s2"}]},
+        {"sysId": "s2", "name": "Hortus", "natId": "ZV-639", "type": "G",
+         "x": 3.0, "y": 4.0, "z": 0.0, "connections": [{"connection": "s1"}]}
+    ]"#;
+    const PLANETS: &str = r#"[
+        {"sysId": "s2", "name": "Montem", "natId": "ZV-639a", "surface": true,
+         "gravity": 0.98, "temp": 22.4, "pressure": 1.01, "fertility": 0.1, "moons": 2}
+    ]"#;
+    const RESOURCES: &str = r#"[
+        {"planet": "ZV-639a", "ticker": "FEO", "type": "MINERAL", "factor": 0.3},
+        {"planet": "ZV-639b", "ticker": "FEO", "type": "MINERAL", "factor": 0.2}
+    ]"#;
+
+    #[test]
+    fn built_survives_the_wire() {
+        let mut universe = Universe::new();
+        universe.stars = serde_json::from_str(STARS).unwrap();
+        universe.planets = serde_json::from_str(PLANETS).unwrap();
+        universe.resources = serde_json::from_str(RESOURCES).unwrap();
+        let built = build(&mut universe).unwrap();
+        // The resource on a missing planet is dropped, and the unknown
+        // field reported
+        assert_eq!(universe.resources.len(), 1);
+        assert_eq!(universe.data_issues.len(), 2);
+
+        let bytes = bincode::serialize(&Response::Built(built)).unwrap();
+        let built = match bincode::deserialize(&bytes).unwrap() {
+            Response::Built(built) => built,
+            other => panic!("{:?}", other),
+        };
+        assert_eq!(unpack(&built.packed).unwrap(), universe.dataset());
+        assert_eq!(built.index, universe.index());
+        assert_eq!(built.cx_distances, universe.cx_distances);
+        assert_eq!(built.cx_distances["s2"].jumps, Some(("ANT", 1)));
+        assert_eq!(built.data_issues, universe.data_issues);
+    }
+}
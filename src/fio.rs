@@ -0,0 +1,316 @@
+// Raw dumps from the FIO community API, turned into the app's own files.
+// Only the fields the app uses are read; the dumps carry many more.
+//
+//     systemstars.json    GET /systemstars
+//     planets.json        GET /planet/allplanets/full
+//     materials.json      GET /material/allmaterials
+//
+// The materials also give the catalogue, json/materials.json.
+//
+// Output is sorted by natural id so the same dumps always give the same
+// files, whatever order the API returned them in.
+use serde_derive::Deserialize;
+use std::collections::HashMap;
+
+use crate::dataset::Dataset;
+use crate::models::{Connection, Material, Planet, Resource, Star};
+
+pub const SYSTEMS_DUMP: &str = "systemstars.json";
+pub const PLANETS_DUMP: &str = "planets.json";
+pub const MATERIALS_DUMP: &str = "materials.json";
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct FioSystem {
+    pub system_id: String,
+    pub name: String,
+    pub natural_id: String,
+    #[serde(rename = "Type")]
+    pub typ: String,
+    pub position_x: f64,
+    pub position_y: f64,
+    pub position_z: f64,
+    #[serde(default)]
+    pub connections: Vec<FioConnection>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct FioConnection {
+    pub connecting_id: String,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct FioPlanet {
+    pub planet_natural_id: String,
+    pub planet_name: String,
+    pub system_id: String,
+    pub surface: bool,
+    pub gravity: f64,
+    pub temperature: f64,
+    pub pressure: f64,
+    pub fertility: f64,
+    #[serde(default)]
+    pub resources: Vec<FioResource>,
+    #[serde(default)]
+    pub has_local_market: bool,
+    #[serde(default)]
+    pub has_chamber_of_commerce: bool,
+    #[serde(default)]
+    pub has_warehouse: bool,
+    #[serde(default)]
+    pub has_administration_center: bool,
+    #[serde(default)]
+    pub has_shipyard: bool,
+}
+
+impl FioPlanet {
+    fn infrastructure(&self) -> Vec<String> {
+        let all = [
+            (self.has_local_market, "localMarket"),
+            (self.has_chamber_of_commerce, "chamberOfCommerce"),
+            (self.has_warehouse, "warehouse"),
+            (self.has_administration_center, "administrationCenter"),
+            (self.has_shipyard, "shipyard"),
+        ];
+        all.iter().filter(|(has, _)| *has).map(|(_, name)| name.to_string()).collect()
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct FioResource {
+    pub material_id: String,
+    pub resource_type: String,
+    pub factor: f64,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct FioMaterial {
+    pub material_id: String,
+    pub ticker: String,
+    // camelCase, eg. ironOre
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub category_name: String,
+    #[serde(default)]
+    pub weight: f64,
+    #[serde(default)]
+    pub volume: f64,
+}
+
+// The material catalogue, sorted by ticker
+pub fn catalogue(materials: &[FioMaterial]) -> Vec<Material> {
+    let mut catalogue: Vec<Material> = materials
+        .iter()
+        .map(|m| Material {
+            ticker: m.ticker.clone(),
+            name: words(&m.name),
+            category: m.category_name.clone(),
+            weight: m.weight,
+            volume: m.volume,
+        })
+        .collect();
+    catalogue.sort_by(|a, b| a.ticker.cmp(&b.ticker));
+    catalogue
+}
+
+// ironOre to Iron Ore
+fn words(name: &str) -> String {
+    let mut out = String::new();
+    let mut prev: Option<char> = None;
+    for c in name.chars() {
+        match prev {
+            None => out.extend(c.to_uppercase()),
+            Some(p) => {
+                if c.is_uppercase() && !p.is_uppercase() {
+                    out.push(' ');
+                }
+                out.push(c);
+            }
+        }
+        prev = Some(c);
+    }
+    out
+}
+
+pub fn convert(
+    systems: &[FioSystem],
+    planets: &[FioPlanet],
+    materials: &[FioMaterial],
+) -> anyhow::Result<Dataset> {
+    let tickers: HashMap<&str, &str> = materials
+        .iter()
+        .map(|m| (m.material_id.as_str(), m.ticker.as_str()))
+        .collect();
+
+    let mut stars: Vec<Star> = systems
+        .iter()
+        .map(|s| {
+            let mut connections: Vec<Connection> = s.connections
+                .iter()
+                .map(|c| Connection { connection: c.connecting_id.clone() })
+                .collect();
+            connections.sort_by(|a, b| a.connection.cmp(&b.connection));
+            connections.dedup();
+            Star {
+                sys_id: s.system_id.clone(),
+                name: s.name.clone(),
+                nat_id: s.natural_id.clone(),
+                typ: s.typ.clone(),
+                x: s.position_x,
+                y: s.position_y,
+                z: s.position_z,
+                connections,
+                ..Star::new()
+            }
+        })
+        .collect();
+    stars.sort_by(|a, b| a.nat_id.cmp(&b.nat_id));
+
+    let mut out_planets = Vec::new();
+    let mut resources = Vec::new();
+    for p in planets {
+        out_planets.push(Planet {
+            sys_id: p.system_id.clone(),
+            name: p.planet_name.clone(),
+            nat_id: p.planet_natural_id.clone(),
+            surface: p.surface,
+            gravity: p.gravity,
+            temp: p.temperature,
+            pressure: p.pressure,
+            fertility: p.fertility,
+            // The dumps don't carry one
+            orbit_index: None,
+            infrastructure: p.infrastructure(),
+            unknown: Default::default(),
+        });
+        for r in &p.resources {
+            let ticker = tickers.get(r.material_id.as_str()).ok_or_else(|| {
+                anyhow::anyhow!("{}: unknown material {}", p.planet_natural_id, r.material_id)
+            })?;
+            resources.push(Resource {
+                planet: p.planet_natural_id.clone(),
+                ticker: ticker.to_string(),
+                typ: r.resource_type.clone(),
+                factor: r.factor,
+                unknown: Default::default(),
+            });
+        }
+    }
+    out_planets.sort_by(|a, b| a.nat_id.cmp(&b.nat_id));
+    resources.sort_by(|a, b| (&a.planet, &a.ticker).cmp(&(&b.planet, &b.ticker)));
+
+    Ok(Dataset {
+        stars,
+        planets: out_planets,
+        resources,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::{PLANETS_FILE, RESOURCES_FILE, STARS_FILE};
+
+    const SYSTEMS: &str = r#"[
+        {"SystemId": "s2", "Name": "Hortus", "NaturalId": "UV-351", "Type": "G",
+         "PositionX": 1.5, "PositionY": -2.25, "PositionZ": 0.1,
+         "Connections": [{"ConnectingId": "s1"}], "Extra": 1},
+        {"SystemId": "s1", "Name": "Antares", "NaturalId": "AN-001", "Type": "M",
+         "PositionX": 0.0, "PositionY": 3.0, "PositionZ": -0.3,
+         "Connections": [{"ConnectingId": "s2"}, {"ConnectingId": "s2"}]}
+    ]"#;
+    const PLANETS: &str = r#"[
+        {"PlanetNaturalId": "UV-351a", "PlanetName": "Montem", "SystemId": "s2",
+         "Surface": true, "Gravity": 0.98, "Temperature": 22.4, "Pressure": 1.01,
+         "Fertility": 0.12, "HasLocalMarket": true,
+         "Resources": [
+            {"MaterialId": "m2", "ResourceType": "GASEOUS", "Factor": 0.08},
+            {"MaterialId": "m1", "ResourceType": "MINERAL", "Factor": 0.31}
+         ]},
+        {"PlanetNaturalId": "AN-001b", "PlanetName": "AN-001b", "SystemId": "s1",
+         "Surface": false, "Gravity": 2.7, "Temperature": -180.0, "Pressure": 30.2,
+         "Fertility": -1.0}
+    ]"#;
+    const MATERIALS: &str = r#"[
+        {"MaterialId": "m1", "Ticker": "FEO", "Name": "ironOre",
+         "CategoryName": "ores", "Weight": 5.9, "Volume": 1.0},
+        {"MaterialId": "m2", "Ticker": "H", "Name": "hydrogen"}
+    ]"#;
+
+    fn dumps() -> (Vec<FioSystem>, Vec<FioPlanet>, Vec<FioMaterial>) {
+        (
+            serde_json::from_str(SYSTEMS).unwrap(),
+            serde_json::from_str(PLANETS).unwrap(),
+            serde_json::from_str(MATERIALS).unwrap(),
+        )
+    }
+
+    fn converted() -> Dataset {
+        let (systems, planets, materials) = dumps();
+        convert(&systems, &planets, &materials).unwrap()
+    }
+
+    fn file<'a>(files: &'a [(&str, String)], name: &str) -> &'a str {
+        &files.iter().find(|(f, _)| *f == name).unwrap().1
+    }
+
+    #[test]
+    fn converts_the_dumps() {
+        let data = converted();
+        let stars: Vec<&str> = data.stars.iter().map(|s| s.nat_id.as_str()).collect();
+        assert_eq!(stars, ["AN-001", "UV-351"]);
+        assert_eq!(data.stars[0].connections, [Connection { connection: "s2".to_string() }]);
+        let planets: Vec<&str> = data.planets.iter().map(|p| p.nat_id.as_str()).collect();
+        assert_eq!(planets, ["AN-001b", "UV-351a"]);
+        assert_eq!(data.planets[1].infrastructure, ["localMarket"]);
+        let resources: Vec<(&str, &str)> = data.resources
+            .iter()
+            .map(|r| (r.planet.as_str(), r.ticker.as_str()))
+            .collect();
+        assert_eq!(resources, [("UV-351a", "FEO"), ("UV-351a", "H")]);
+    }
+
+    #[test]
+    fn files_read_back_the_same() {
+        let data = converted();
+        let files = data.to_files().unwrap();
+        let read = Dataset {
+            stars: serde_json::from_str(file(&files, STARS_FILE)).unwrap(),
+            planets: serde_json::from_str(file(&files, PLANETS_FILE)).unwrap(),
+            resources: serde_json::from_str(file(&files, RESOURCES_FILE)).unwrap(),
+        };
+        assert_eq!(read, data);
+    }
+
+    #[test]
+    fn same_dumps_same_bytes() {
+        let first = converted().to_files().unwrap();
+        assert_eq!(converted().to_files().unwrap(), first);
+        // Nor does the order the API returned them in matter
+        let (mut systems, mut planets, mut materials) = dumps();
+        systems.reverse();
+        planets.reverse();
+        materials.reverse();
+        let reordered = convert(&systems, &planets, &materials).unwrap();
+        assert_eq!(reordered.to_files().unwrap(), first);
+    }
+
+    #[test]
+    fn unknown_material_is_an_error() {
+        let (systems, planets, materials) = dumps();
+        assert!(convert(&systems, &planets, &materials[..1]).is_err());
+    }
+
+    #[test]
+    fn names_split_into_words() {
+        assert_eq!(words("ironOre"), "Iron Ore");
+        assert_eq!(words("hydrogen"), "Hydrogen");
+        let tickers: Vec<String> = catalogue(&dumps().2).into_iter().map(|m| m.ticker).collect();
+        assert_eq!(tickers, ["FEO", "H"]);
+    }
+}
@@ -0,0 +1,67 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::models::Star;
+
+// Jumps from the origin system to every system reachable from it, by
+// sys_id. A breadth first search over Star.connections.
+pub fn jump_distances(stars: &[Star], origin: &str) -> HashMap<String, u32> {
+    let by_id: HashMap<&str, &Star> = stars
+        .iter()
+        .map(|s| (s.sys_id.as_str(), s))
+        .collect();
+    let mut jumps = HashMap::new();
+    if !by_id.contains_key(origin) {
+        return jumps;
+    }
+    let mut queue = VecDeque::new();
+    jumps.insert(origin.to_string(), 0);
+    queue.push_back(origin);
+    while let Some(sys_id) = queue.pop_front() {
+        let j = jumps[sys_id];
+        for c in &by_id[sys_id].connections {
+            let next = c.connection.as_str();
+            if by_id.contains_key(next) && !jumps.contains_key(next) {
+                jumps.insert(next.to_string(), j + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+    jumps
+}
+
+// The sys_ids on a shortest route by jumps, both ends included, or None if
+// the destination can't be reached
+pub fn route(stars: &[Star], from: &str, to: &str) -> Option<Vec<String>> {
+    let by_id: HashMap<&str, &Star> = stars
+        .iter()
+        .map(|s| (s.sys_id.as_str(), s))
+        .collect();
+    if !by_id.contains_key(from) || !by_id.contains_key(to) {
+        return None;
+    }
+    // Each system reached by the one it was reached from
+    let mut previous: HashMap<&str, &str> = HashMap::new();
+    let mut queue = VecDeque::new();
+    previous.insert(from, from);
+    queue.push_back(from);
+    while let Some(sys_id) = queue.pop_front() {
+        if sys_id == to {
+            let mut path = vec![to.to_string()];
+            let mut at = to;
+            while at != from {
+                at = previous[at];
+                path.push(at.to_string());
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for c in &by_id[sys_id].connections {
+            let next = c.connection.as_str();
+            if by_id.contains_key(next) && !previous.contains_key(next) {
+                previous.insert(next, sys_id);
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,19 @@
+//! The PrUn Rusty Little Helper data model and the searches over it, shared
+//! by the web app and by tools that want the same answers outside the
+//! browser.
+
+pub mod dataset;
+pub mod diff;
+pub mod export;
+pub mod extraction;
+pub mod filter_worker;
+pub mod fio;
+pub mod graph;
+pub mod manifest;
+pub mod models;
+pub mod pack;
+pub mod query;
+pub mod schema;
+pub mod shared;
+pub mod stats;
+pub mod validate;
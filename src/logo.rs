@@ -3,6 +3,11 @@ use yew::prelude::*;
 #[derive(Clone, PartialEq, Eq, Properties)]
 pub struct Props {
     pub is_loading: bool,
+    // From the data manifest, YYYY-MM-DD
+    pub data_as_of: Option<String>,
+    // The manifest couldn't be fetched, so the data is whatever was cached
+    pub offline: bool,
+    pub load_failed: bool,
 }
 
 #[allow(dead_code)]
@@ -33,16 +38,32 @@ impl Component for Logo {
     }
 
     fn view(&self) -> Html {
-        let loading_text = match self.props.is_loading {
-            true => "Calculating Star and Planet data. Please wait...",
+        let loading_text = match (self.props.load_failed, self.props.is_loading) {
+            (true, _) => "Unable to load the data, see the console",
+            (_, true) => "Calculating Star and Planet data. Please wait...",
             _=> "",
         };
 
+        let data_as_of = match &self.props.data_as_of {
+            Some(date) => html! {
+                <span class="data-as-of" title="Date the game data was taken">
+                    {format!("Data as of {}", date)}
+                    { if self.props.offline {
+                        html! { <span class="offline">{" (offline)"}</span> }
+                    } else {
+                        html! {}
+                    }}
+                </span>
+            },
+            None => html! {},
+        };
+
         html! {
             <>
                 <div class="logo">
                     <h1>{"Plexucra"}<span class="cc">{".de"}</span></h1>
                     <span class="byline">{"Tools for the Galactic CEO"}</span>
+                    {data_as_of}
                 </div>
                 <h3>{loading_text}</h3>
             </>
@@ -1,11 +1,28 @@
 #![recursion_limit = "256"]
 
+use serde::de::DeserializeOwned;
+use std::time::Duration;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
 use yew::format::{Json, Nothing};
 use yew::prelude::*;
-use yew::services::fetch::{FetchService, FetchTask, Request, Response};
+use yew::services::fetch::{Cache, FetchOptions, FetchService, FetchTask, Request, Response, StatusCode};
+use yew::services::timeout::{TimeoutService, TimeoutTask};
+use yew::services::ConsoleService;
 
-mod models;
+use prun_rusty_little_helper::{dataset, diff, export, extraction, filter_worker, graph, manifest, models, pack, query, schema, shared, stats};
+use dataset::{MATERIALS_FILE, PLANETS_FILE, RESOURCES_FILE, STARS_FILE};
+use diff::{changed_systems, Change};
+use manifest::{Manifest, MANIFEST_FILE};
+use pack::{unpack, PACK_FILE};
+use schema::SCHEMA_VERSION;
+use shared::Shared;
+use extraction::ExtractionSettings;
+use filter_worker::FilterWorker;
 use models::*;
+use query::*;
+mod query_bar;
+use query_bar::*;
 mod canvas;
 use canvas::*;
 mod logo;
@@ -18,6 +35,8 @@ mod show_cx;
 use show_cx::*;
 mod show_routes;
 use show_routes::*;
+mod rank_percentile;
+use rank_percentile::*;
 mod edit_filters;
 use edit_filters::*;
 mod system;
@@ -25,27 +44,89 @@ use system::*;
 mod planet_details;
 mod summary;
 use summary::*;
+mod results_table;
+use results_table::*;
+mod planet_view;
+use planet_view::*;
+mod extraction_edit;
+use extraction_edit::*;
+mod res_dashboard;
+use res_dashboard::*;
+mod compare;
+use compare::*;
+mod favourites;
+use favourites::*;
+mod favourites_panel;
+use favourites_panel::*;
+mod bases;
+use bases::*;
+mod my_bases;
+use my_bases::*;
+mod snapshot_diff;
+use snapshot_diff::*;
+mod reach_filter;
+use reach_filter::*;
+mod download;
+mod export_results;
+use export_results::*;
+mod presets;
+use presets::*;
+mod filter_presets;
+use filter_presets::*;
+mod url_state;
+use url_state::*;
+mod data_cache;
+use data_cache::ManifestStore;
+
+const DATA_URL: &str = "http://localhost:8081/";
+//const DATA_URL: &str = "http://nimbus.tosp.net.au/json/";
 
-// TODO sort out what should be in Universe and what should be in PrUnApp
 struct PrUnApp {
     is_loading: bool,
-    universe: Universe,
+    // The data, only changed as it loads, and what's selected and filtered
+    universe: Shared<Universe>,
+    view: View,
+    worker: Box<dyn Bridge<FilterWorker>>,
+    // The latest filter request, and whether it's still being worked on
+    filter_seq: u32,
+    filtering: bool,
     map_features: MapFeatures,
     filters: Filters,
-    fetch_stars: Option<FetchTask>,
-    fetch_planets: Option<FetchTask>,
-    fetch_resources: Option<FetchTask>,
+    presets: PresetStore,
+    favourites: FavouriteStore,
+    bases: BaseStore,
+    // An earlier snapshot's name and what has changed since it, or why
+    // it couldn't be compared
+    snapshot_diff: Option<(String, Vec<Change>)>,
+    snapshot_error: Option<String>,
+    url_star: Option<String>,
+    url_pan: Option<(f64, f64)>,
+    scroll_task: Option<TimeoutTask>,
+    _on_scroll: Option<Closure<dyn Fn()>>,
+    manifest: ManifestStore,
+    offline: bool,
+    load_failed: bool,
+    fetch_manifest: Option<FetchTask>,
+    selected_planet: Option<String>,
+    pinned: Vec<String>,
+    extraction: ExtractionSettings,
     link: ComponentLink<Self>,
 }
 
 #[allow(dead_code)]
 enum Msg {
+    MakeManifestReq,
+    MakePackReq,
     MakeStarReq,
     MakePlanetReq,
     MakeResourceReq,
-    RespStar(Result<Vec<Star>, anyhow::Error>),
-    RespPlanet(Result<Vec<Planet>, anyhow::Error>),
-    RespResource(Result<Vec<Resource>, anyhow::Error>),
+    MakeBaseReq,
+    MakeMaterialReq,
+    RespManifest(Option<Manifest>, bool),
+    RespData(&'static str, Result<Vec<u8>, anyhow::Error>),
+    RespBase(Result<BaseMaterials, anyhow::Error>),
+    RespMaterial(Result<Vec<Material>, anyhow::Error>),
+    Worker(filter_worker::Response),
     SelectedStar(Star),
     SetScale(f64),
     SearchStar(String),
@@ -53,6 +134,18 @@ enum Msg {
     Surface(SurfaceOption),
     Environment((Environment, EnvironmentOption)),
     SelectedRes(Option<String>),
+    SelectedPlanet(Option<String>),
+    Pin(String),
+    Favourite(FavouriteAction),
+    Base(BaseAction),
+    Reach(ReachAction),
+    Snapshot(SnapshotAction),
+    Extraction(ExtractionSettings),
+    Preset(PresetAction),
+    ResetFilters,
+    Query(Query),
+    Scrolled,
+    WriteUrl,
     TestMe,
 }
 
@@ -61,137 +154,262 @@ impl Component for PrUnApp {
     type Properties = ();
 
     fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
-        link.send_message(Msg::MakeStarReq);
-        link.send_message(Msg::MakePlanetReq);
-        link.send_message(Msg::MakeResourceReq);
-        Self {
+        link.send_message(Msg::MakeManifestReq);
+        let presets = PresetStore::new();
+        let mut view = View::new();
+        let mut map_features = MapFeatures::new();
+        let mut filters = Filters::new();
+        let mut url_star = None;
+        let mut url_pan = None;
+        // A shared link wins over whatever was used last time
+        if let Some(state) = UrlState::from_location() {
+            filters = state.filters;
+            view.selected_res = state.selected_res;
+            map_features.set_selected_scale(state.scale);
+            map_features.show_cx = state.show_cx;
+            map_features.show_routes = state.show_routes;
+            view.normalisation = state.normalisation;
+            url_star = state.selected_star;
+            url_pan = Some(state.pan);
+        } else {
+            if let Some(last_used) = presets.last_used() {
+                filters = last_used.filters;
+                view.selected_res = last_used.selected_res;
+            }
+            if let Some(settings) = presets.last_map() {
+                settings.apply(&mut map_features);
+            }
+        }
+        let on_scroll = web_sys::window().map(|window| {
+            let scroll_link = link.clone();
+            let on_scroll = Closure::wrap(Box::new(move || {
+                scroll_link.send_message(Msg::Scrolled)
+            }) as Box<dyn Fn()>);
+            window.set_onscroll(Some(on_scroll.as_ref().unchecked_ref()));
+            on_scroll
+        });
+        let worker = FilterWorker::bridge(link.callback(Msg::Worker));
+        let mut app = Self {
             is_loading: true,
-            universe: Universe::new(),
-            map_features: MapFeatures::new(),
-            filters: Filters::new(),
-            fetch_stars: None,
-            fetch_planets: None,
-            fetch_resources: None,
+            universe: Shared::new(Universe::new()),
+            view,
+            worker,
+            filter_seq: 0,
+            filtering: false,
+            map_features,
+            filters,
+            presets,
+            favourites: FavouriteStore::new(),
+            bases: BaseStore::new(),
+            snapshot_diff: None,
+            snapshot_error: None,
+            url_star,
+            url_pan,
+            scroll_task: None,
+            _on_scroll: on_scroll,
+            manifest: ManifestStore::new(),
+            offline: false,
+            load_failed: false,
+            fetch_manifest: None,
+            selected_planet: None,
+            pinned: Vec::new(),
+            extraction: ExtractionSettings::new(),
             link,
-        }
+        };
+        // Answered once the worker has the data
+        app.apply_filters();
+        app
     }
 
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match msg {
-            Msg::MakeStarReq => {
+            // The manifest always comes from the server when it can be
+            // reached, and says which version of the data files to use
+            Msg::MakeManifestReq => {
                 self.is_loading = true;
-                let req = Request::get("http://localhost:8081/stars.json")
-                    //let req = Request::get("http://nimbus.tosp.net.au/json/stars.json")
+                let req = Request::get(format!("{}{}", DATA_URL, MANIFEST_FILE))
                     .body(Nothing)
                     .expect("can make req");
+                let options = FetchOptions {
+                    cache: Some(Cache::NoCache),
+                    ..FetchOptions::default()
+                };
 
                 let cb = self.link.callback(
-                    |response: Response<Json<Result<Vec<Star>, anyhow::Error>>>| {
-                        let Json(data) = response.into_body();
-                        Msg::RespStar(data)
+                    |response: Response<Json<Result<Manifest, anyhow::Error>>>| {
+                        // yew reports a failed fetch as a timeout
+                        let status = response.status();
+                        match response.into_body() {
+                            Json(Ok(manifest)) if status.is_success() => Msg::RespManifest(Some(manifest), false),
+                            _ => Msg::RespManifest(None, status == StatusCode::REQUEST_TIMEOUT),
+                        }
                     },
                 );
 
-                let task = FetchService::fetch(req, cb).expect("can create task");
-                self.fetch_stars = Some(task);
+                match FetchService::fetch_with_options(req, options, cb) {
+                    Ok(task) => self.fetch_manifest = Some(task),
+                    Err(_) => self.link.send_message(Msg::RespManifest(None, true)),
+                }
+                false
+            }
+            Msg::RespManifest(manifest, offline) => {
+                self.fetch_manifest = None;
+                self.offline = offline;
+                match manifest {
+                    Some(manifest) => {
+                        if manifest.schema_version > SCHEMA_VERSION {
+                            ConsoleService::warn(&format!(
+                                "Data schema {} is newer than this app's {}, new fields are ignored",
+                                manifest.schema_version, SCHEMA_VERSION));
+                        }
+                        data_cache::prune(manifest.version.clone());
+                        self.manifest.set(manifest);
+                    }
+                    None => ConsoleService::info("No manifest, using the last one seen"),
+                }
+                self.link.send_message(Msg::MakePackReq);
+                self.link.send_message(Msg::MakeBaseReq);
+                self.link.send_message(Msg::MakeMaterialReq);
+                true
+            }
+            // The packed dataset if the server has one, else the JSON files
+            Msg::MakePackReq => {
+                self.is_loading = true;
+                self.fetch_data(PACK_FILE, |resp| Msg::RespData(PACK_FILE, resp));
+                false
+            }
+            // The big files go straight to the filter worker, which reads
+            // them and sends the universe back once it has them all
+            Msg::RespData(file, resp) => match resp {
+                Ok(bytes) => {
+                    self.worker.send(filter_worker::Request::Load(file.to_string(), bytes));
+                    false
+                }
+                Err(e) => self.not_loaded(file, format!("{:#}", e)),
+            },
+            Msg::MakeStarReq => {
+                self.is_loading = true;
+                self.fetch_data(STARS_FILE, |resp| Msg::RespData(STARS_FILE, resp));
                 false
             }
             Msg::MakePlanetReq => {
                 self.is_loading = true;
-                let req = Request::get("http://localhost:8081/planets.json")
-                    //let req = Request::get("http://nimbus.tosp.net.au/json/planets.json")
-                    .body(Nothing)
-                    .expect("can make req");
-
-                let cb = self.link.callback(
-                    |response: Response<Json<Result<Vec<Planet>, anyhow::Error>>>| {
-                        let Json(data) = response.into_body();
-                        Msg::RespPlanet(data)
-                    },
-                );
-
-                let task = FetchService::fetch(req, cb).expect("can create task");
-                self.fetch_planets = Some(task);
+                self.fetch_data(PLANETS_FILE, |resp| Msg::RespData(PLANETS_FILE, resp));
                 false
             }
             Msg::MakeResourceReq => {
                 self.is_loading = true;
-                let req = Request::get("http://localhost:8081/resources.json")
-                    //let req = Request::get("http://nimbus.tosp.net.au/json/resources.json")
-                    .body(Nothing)
-                    .expect("can make req");
-
-                let cb = self.link.callback(
-                    |response: Response<Json<Result<Vec<Resource>, anyhow::Error>>>| {
-                        let Json(data) = response.into_body();
-                        Msg::RespResource(data)
-                    },
-                );
-
-                let task = FetchService::fetch(req, cb).expect("can create task");
-                self.fetch_resources = Some(task);
+                self.fetch_data(RESOURCES_FILE, |resp| Msg::RespData(RESOURCES_FILE, resp));
                 false
             }
-            Msg::RespStar(resp) => {
-                if let Ok(data) = resp {
-                    self.universe.stars = data;
-                    self.universe.fix_star_y();
-                    self.is_loading = false;
+            Msg::MakeBaseReq => {
+                self.fetch_data("base.json", |resp| Msg::RespBase(from_json(resp)));
+                false
+            }
+            Msg::MakeMaterialReq => {
+                self.fetch_data(MATERIALS_FILE, |resp| Msg::RespMaterial(from_json(resp)));
+                false
+            }
+            Msg::RespBase(resp) => {
+                if let Some(data) = self.loaded("base materials", resp) {
+                    self.universe.make_mut().base_materials = data;
                 }
-                self.universe.apply_filters(&self.filters);
                 true
             }
-            Msg::RespPlanet(resp) => {
-                if let Ok(data) = resp {
-                    self.universe.planets = data;
-                    self.universe.create_star_list();
-                    self.is_loading = false;
+            Msg::RespMaterial(resp) => {
+                if let Some(data) = self.loaded("material catalogue", resp) {
+                    self.universe.make_mut().materials = data.into_iter().map(|m| (m.ticker.clone(), m)).collect();
                 }
-                self.universe.apply_filters(&self.filters);
                 true
             }
-            Msg::RespResource(resp) => {
-                if let Ok(data) = resp {
-                    self.universe.resources = data;
-                    self.universe.create_resource_data();
-                    self.is_loading = false;
+            Msg::Worker(filter_worker::Response::Built(built)) => {
+                self.is_loading = false;
+                let data = match unpack(&built.packed) {
+                    Ok(data) => data,
+                    Err(e) => return self.not_loaded("universe", format!("{:#}", e)),
+                };
+                let universe = self.universe.make_mut();
+                universe.stars = data.stars;
+                universe.planets = data.planets;
+                universe.resources = data.resources;
+                universe.set_index(built.index);
+                universe.cx_distances = built.cx_distances;
+                universe.data_issues = built.data_issues;
+                for issue in &self.universe.data_issues {
+                    ConsoleService::warn(&issue.to_string());
+                }
+                if let Some(nat_id) = self.url_star.take() {
+                    self.view.selected_star = self.universe.stars
+                        .iter()
+                        .find(|s| s.nat_id == nat_id)
+                        .cloned();
                 }
-                self.universe.apply_filters(&self.filters);
+                true
+            }
+            Msg::Worker(filter_worker::Response::Failed(file, e)) => self.not_loaded(&file, e),
+            Msg::Worker(filter_worker::Response::Filtered(seq, results)) => {
+                // Anything older has been overtaken by a later change
+                if seq != self.filter_seq {
+                    return false;
+                }
+                self.view.results = Shared::new(results);
+                self.filtering = false;
                 true
             }
             Msg::SelectedStar(star) => {
-                self.universe.selected_star = Some(star);
+                self.close_planet_outside(&star);
+                self.view.selected_star = Some(star);
+                self.write_url();
                 true
             }
             Msg::SetScale(scale) => {
                 self.map_features.set_selected_scale(scale);
+                self.map_changed();
                 true
             }
             Msg::SearchStar(name) => {
                 if let Some(star) = self.universe.star_from_name(name) {
                     //self.link.callback(Msg::SelectedStar).emit(star);
-                    self.universe.selected_star = Some(star);
+                    self.close_planet_outside(&star);
+                    self.view.selected_star = Some(star);
+                    self.write_url();
+                    true
+                } else {
+                    false
                 }
-                false
             }
             Msg::Toggle((toggle, b)) => {
                 match toggle {
-                    Toggle::ShowCx => self.map_features.show_cx = b,
-                    Toggle::ShowRoutes => self.map_features.show_routes = b,
+                    Toggle::ShowCx => {
+                        self.map_features.show_cx = b;
+                        self.map_changed();
+                    }
+                    Toggle::ShowRoutes => {
+                        self.map_features.show_routes = b;
+                        self.map_changed();
+                    }
                     Toggle::IncEnvFilter => {
                         self.filters.env_filter = b;
-                        self.universe.apply_filters(&self.filters);
+                        self.filters_changed();
                     }
                     Toggle::IncNormal => {
                         self.filters.inc_normal = b;
-                        self.universe.apply_filters(&self.filters);
+                        self.filters_changed();
+                    }
+                    Toggle::Percentile => {
+                        self.view.normalisation = if b {
+                            Normalisation::Percentile
+                        } else {
+                            Normalisation::PercentOfMax
+                        };
+                        self.write_url();
                     }
                 };
                 true
             }
             Msg::Surface(surface) => {
                 self.filters.surface = surface;
-                self.universe.apply_filters(&self.filters);
+                self.filters_changed();
                 true
             }
             Msg::Environment((env, option)) => {
@@ -200,14 +418,152 @@ impl Component for PrUnApp {
                     Environment::Temp => self.filters.temp = option,
                     Environment::Pressure => self.filters.pressure = option,
                 }
-                self.universe.apply_filters(&self.filters);
+                self.filters_changed();
                 true
             }
             Msg::SelectedRes(res) => {
-                self.universe.selected_res = res;
-                self.universe.apply_filters(&self.filters);
+                self.view.selected_res = res;
+                self.filters_changed();
+                true
+            }
+            Msg::SelectedPlanet(planet) => {
+                self.selected_planet = planet;
+                true
+            }
+            Msg::Favourite(action) => {
+                match action {
+                    FavouriteAction::Toggle(kind, nat_id) => {
+                        let found = match kind {
+                            FavouriteKind::Planet => self.universe
+                                .planet_from_id(&nat_id)
+                                .map(|p| (p.name.clone(), self.universe.star_for_planet(p))),
+                            FavouriteKind::System => self.universe.stars
+                                .iter()
+                                .find(|s| s.nat_id == nat_id)
+                                .map(|s| (s.name.clone(), Some(s))),
+                        };
+                        if let Some((name, star)) = found {
+                            let favourite = Favourite {
+                                kind,
+                                name,
+                                system: star.map(|s| s.nat_id.clone()).unwrap_or_default(),
+                                note: String::new(),
+                            };
+                            self.favourites.toggle(nat_id, favourite);
+                        }
+                    }
+                    FavouriteAction::Note(nat_id, note) => self.favourites.set_note(&nat_id, note),
+                    FavouriteAction::Import(favourites) => self.favourites.import(favourites),
+                }
+                true
+            }
+            Msg::Base(action) => {
+                match action {
+                    BaseAction::Add(nat_id) => self.bases.add(nat_id),
+                    BaseAction::Remove(nat_id) => self.bases.remove(&nat_id),
+                }
+                true
+            }
+            Msg::Pin(nat_id) => {
+                match self.pinned.iter().position(|p| p == &nat_id) {
+                    Some(i) => {
+                        self.pinned.remove(i);
+                    }
+                    None => {
+                        if self.pinned.len() == MAX_PINNED {
+                            self.pinned.remove(0);
+                        }
+                        self.pinned.push(nat_id);
+                    }
+                }
+                true
+            }
+            // Compared by the worker, which has the data as it was loaded
+            Msg::Snapshot(SnapshotAction::Load(name, bytes)) => {
+                self.worker.send(filter_worker::Request::Diff(name, bytes));
+                false
+            }
+            Msg::Snapshot(SnapshotAction::Clear) => {
+                self.snapshot_diff = None;
+                self.snapshot_error = None;
+                true
+            }
+            Msg::Worker(filter_worker::Response::Diffed(name, changes)) => {
+                match changes {
+                    Ok(changes) => {
+                        self.snapshot_diff = Some((name, changes));
+                        self.snapshot_error = None;
+                    }
+                    Err(e) => self.snapshot_error = Some(format!("{}: {}", name, e)),
+                }
                 true
             }
+            Msg::Extraction(settings) => {
+                self.extraction = settings;
+                true
+            }
+            Msg::Query(query) => {
+                self.filters = query.filters;
+                self.view.selected_res = query.selected_res;
+                self.filters_changed();
+                true
+            }
+            // A single reach from the panel replaces any from the query
+            Msg::Reach(action) => {
+                self.filters.jump_filters.clear();
+                self.filters.radius_filters.clear();
+                if let ReachAction::Set(origin, mode, limit) = action {
+                    match mode {
+                        ReachMode::Jumps => self.filters.jump_filters.push(JumpFilter {
+                            origin,
+                            cmp: Comparison::Le,
+                            jumps: limit.round() as u32,
+                        }),
+                        ReachMode::Radius => self.filters.radius_filters.push(RadiusFilter {
+                            origin,
+                            cmp: Comparison::Le,
+                            radius: limit,
+                        }),
+                    }
+                }
+                self.filters_changed();
+                true
+            }
+            Msg::ResetFilters => {
+                self.filters = Filters::new();
+                self.view.selected_res = None;
+                self.filters_changed();
+                true
+            }
+            Msg::Preset(action) => {
+                match action {
+                    PresetAction::Save(name) => {
+                        let preset = self.current_preset();
+                        self.presets.save(name, preset);
+                    }
+                    PresetAction::Load(name) => {
+                        if let Some(preset) = self.presets.get(&name) {
+                            self.filters = preset.filters;
+                            self.view.selected_res = preset.selected_res;
+                            self.filters_changed();
+                        }
+                    }
+                    PresetAction::Rename(from, to) => self.presets.rename(&from, to),
+                    PresetAction::Delete(name) => self.presets.delete(&name),
+                }
+                true
+            }
+            Msg::Scrolled => {
+                // Only record the pan once scrolling has settled
+                let cb = self.link.callback(|_| Msg::WriteUrl);
+                self.scroll_task = Some(TimeoutService::spawn(Duration::from_millis(300), cb));
+                false
+            }
+            Msg::WriteUrl => {
+                self.scroll_task = None;
+                self.write_url();
+                false
+            }
             Msg::TestMe => {
                 //self.filter_editor.apply_filters(&self.universe.planets);
                 true
@@ -219,23 +575,53 @@ impl Component for PrUnApp {
         false
     }
 
+    fn rendered(&mut self, first_render: bool) {
+        if first_render {
+            if let Some(pan) = self.url_pan.take() {
+                scroll_to(pan);
+            }
+        }
+    }
+
+    // html! expands the props of a component into statements clippy
+    // mistakes for no-ops
+    #[allow(clippy::unnecessary_operation)]
     fn view(&self) -> Html {
         html! {
             <>
                 <Canvas
                     map_features=&self.map_features
                     universe=&self.universe
+                    view=&self.view
                     env_filter=self.filters.env_filter
+                    favourites=self.favourites.all().clone()
+                    bases=self.bases.all().clone()
+                    selected_planet=self.selected_planet.clone()
+                    pinned=self.pinned.clone()
+                    pin_signal=self.link.callback(Msg::Pin)
+                    changed_systems=self.snapshot_diff.as_ref()
+                        .map(|(_, changes)| changed_systems(changes))
+                        .unwrap_or_default()
                     selected_star_signal=self.link.callback(Msg::SelectedStar)
                 />
                 <div class="app">
                     <div class="panel1">
                         <div class="sticky">
-                            <Logo is_loading=self.is_loading />
+                            <Logo
+                                is_loading=self.is_loading
+                                data_as_of=self.manifest.get().map(|m| m.data_as_of.clone())
+                                offline=self.offline
+                                load_failed=self.load_failed
+                            />
                             <MapScale
                                 scale_options=self.map_features.scale_options.clone()
                                 set_scale_signal=self.link.callback(Msg::SetScale)
                             />
+                            <ResourceDashboard
+                                universe=&self.universe
+                                view=&self.view
+                                search_star_signal=self.link.callback(Msg::SearchStar)
+                            />
                         </div>
                     </div>
                     <div class="panel2">
@@ -244,6 +630,29 @@ impl Component for PrUnApp {
                                 star_list=self.universe.star_list.clone()
                                 search_star_signal=self.link.callback(Msg::SearchStar)
                             />
+                            <QueryBar
+                                tickers=self.universe.res_list.clone()
+                                query_signal=self.link.callback(Msg::Query)
+                            />
+                            <ResultsTable
+                                universe=&self.universe
+                                view=&self.view
+                                extraction=self.extraction
+                                pinned=self.pinned.clone()
+                                search_star_signal=self.link.callback(Msg::SearchStar)
+                                pin_signal=self.link.callback(Msg::Pin)
+                            />
+                            <ExportResults
+                                universe=&self.universe
+                                view=&self.view
+                            />
+                            <PlanetCompare
+                                universe=&self.universe
+                                pinned=self.pinned.clone()
+                                extraction=self.extraction
+                                unpin_signal=self.link.callback(Msg::Pin)
+                                search_star_signal=self.link.callback(Msg::SearchStar)
+                            />
                         </div>
                     </div>
                     <div class="panel3">
@@ -256,27 +665,88 @@ impl Component for PrUnApp {
                                 show_routes=self.map_features.show_routes
                                 toggle_signal=self.link.callback(Msg::Toggle)
                             />
+                            <RankPercentile
+                                percentile=self.view.normalisation == Normalisation::Percentile
+                                toggle_signal=self.link.callback(Msg::Toggle)
+                            />
+                            <FilterPresets
+                                presets=self.presets.names()
+                                preset_signal=self.link.callback(Msg::Preset)
+                            />
                             <EditFilters
-                                env_filter=self.filters.env_filter
-                                inc_normal=self.filters.inc_normal
-                                res_list=self.universe.res_list.clone()
+                                filters=&self.filters
+                                selected_res=self.view.selected_res.clone()
+                                res_groups=self.universe.res_groups()
                                 toggle_signal=self.link.callback(Msg::Toggle)
                                 surface_signal=self.link.callback(Msg::Surface)
                                 env_signal=self.link.callback(Msg::Environment)
                                 selected_res_signal=self.link.callback(Msg::SelectedRes)
+                                reset_signal=self.link.callback(|_| Msg::ResetFilters)
+                            />
+                            <ReachFilter
+                                filters=&self.filters
+                                selected_star=self.view.selected_star.clone()
+                                reach_signal=self.link.callback(Msg::Reach)
+                            />
+                            <ExtractionEdit
+                                settings=self.extraction
+                                settings_signal=self.link.callback(Msg::Extraction)
                             />
                             <Summary
                                 universe=&self.universe
-                                env_filter=self.filters.env_filter
+                                view=&self.view
+                                busy=self.filtering
+                                pinned=self.pinned.clone()
+                                pin_signal=self.link.callback(Msg::Pin)
+                                query=to_query(&self.filters, &self.view.selected_res)
+                            />
+                            <FavouritesPanel
+                                favourites=self.favourites.all().clone()
+                                favourite_signal=self.link.callback(Msg::Favourite)
+                                search_star_signal=self.link.callback(Msg::SearchStar)
+                            />
+                            <MyBases
+                                universe=&self.universe
+                                bases=self.bases.all().clone()
+                                base_signal=self.link.callback(Msg::Base)
+                                search_star_signal=self.link.callback(Msg::SearchStar)
+                            />
+                            <SnapshotDiff
+                                universe=&self.universe
+                                diff=self.snapshot_diff.clone()
+                                error=self.snapshot_error.clone()
+                                snapshot_signal=self.link.callback(Msg::Snapshot)
                                 search_star_signal=self.link.callback(Msg::SearchStar)
                             />
                         </div>
                     </div>
                     <div class="panel4">
                         <div class="sticky">
+                            { if let Some(planet_id) = &self.selected_planet {
+                                html! {
+                                    <PlanetView
+                                        universe=&self.universe
+                                        planet_id=planet_id.clone()
+                                        extraction=self.extraction
+                                        pinned=self.pinned.contains(planet_id)
+                                        close_signal=self.link.callback(|_| Msg::SelectedPlanet(None))
+                                        pin_signal=self.link.callback(Msg::Pin)
+                                        search_star_signal=self.link.callback(Msg::SearchStar)
+                                    />
+                                }
+                            } else {
+                                html! {}
+                            }}
                             <System
                                 universe=&self.universe
+                                view=&self.view
                                 env_filter=self.filters.env_filter
+                                extraction=self.extraction
+                                pinned=self.pinned.clone()
+                                favourites=self.favourites.all().clone()
+                                planet_signal=self.link.callback(|p| Msg::SelectedPlanet(Some(p)))
+                                pin_signal=self.link.callback(Msg::Pin)
+                                favourite_signal=self.link.callback(Msg::Favourite)
                             />
                         </div>
                     </div>
@@ -286,6 +756,90 @@ impl Component for PrUnApp {
     }
 }
 
+impl PrUnApp {
+    // Through the cache for the current manifest version
+    fn fetch_data<F>(&self, file: &str, to_msg: F)
+    where
+        F: Fn(Result<Vec<u8>, anyhow::Error>) -> Msg + 'static,
+    {
+        let url = format!("{}{}", DATA_URL, file);
+        data_cache::fetch_cached(url, self.manifest.version(), self.link.callback(to_msg));
+    }
+    // Say why a file didn't load rather than just carry on without it
+    fn loaded<T>(&mut self, what: &str, resp: Result<T, anyhow::Error>) -> Option<T> {
+        match resp {
+            Ok(data) => Some(data),
+            Err(e) => {
+                ConsoleService::error(&format!("Unable to load the {}: {:#}", what, e));
+                self.load_failed = true;
+                None
+            }
+        }
+    }
+    // Without the packed dataset the JSON files will do, without anything
+    // else there's nothing to show
+    fn not_loaded(&mut self, file: &str, error: String) -> ShouldRender {
+        if file == PACK_FILE {
+            ConsoleService::info(&format!("Loading JSON instead: {}", error));
+            self.link.send_message(Msg::MakeStarReq);
+            self.link.send_message(Msg::MakePlanetReq);
+            self.link.send_message(Msg::MakeResourceReq);
+            return false;
+        }
+        ConsoleService::error(&format!("Unable to load the {}: {}", file, error));
+        self.is_loading = false;
+        self.load_failed = true;
+        true
+    }
+    fn current_preset(&self) -> Preset {
+        Preset {
+            filters: self.filters.clone(),
+            selected_res: self.view.selected_res.clone(),
+        }
+    }
+    fn apply_filters(&mut self) {
+        self.filter_seq += 1;
+        self.filtering = true;
+        self.worker.send(filter_worker::Request::Filter(
+            self.filter_seq,
+            self.filters.clone(),
+            self.view.selected_res.clone(),
+        ));
+    }
+    fn filters_changed(&mut self) {
+        self.apply_filters();
+        let preset = self.current_preset();
+        self.presets.store_last_used(&preset);
+        self.write_url();
+    }
+    fn map_changed(&mut self) {
+        self.presets.store_last_map(&MapSettings::of(&self.map_features));
+        self.write_url();
+    }
+    // The planet view follows the selected system
+    fn close_planet_outside(&mut self, star: &Star) {
+        let outside = match &self.selected_planet {
+            Some(id) => self.universe
+                .planet_from_id(id)
+                .is_none_or(|p| p.sys_id != star.sys_id),
+            None => false,
+        };
+        if outside {
+            self.selected_planet = None;
+        }
+    }
+    fn write_url(&self) {
+        // Don't clobber a shared link before its star has been restored
+        if self.url_star.is_none() {
+            UrlState::new(&self.filters, &self.view, &self.map_features).write_location();
+        }
+    }
+}
+
+fn from_json<T: DeserializeOwned>(resp: Result<Vec<u8>, anyhow::Error>) -> Result<T, anyhow::Error> {
+    Ok(serde_json::from_slice(&resp?)?)
+}
+
 pub fn main() {
     yew::start_app::<PrUnApp>();
 }
@@ -0,0 +1,45 @@
+// manifest.json sits next to the data files and says which version of the
+// data they are, so a cached copy is only used while it's still current.
+use serde_derive::{Deserialize, Serialize};
+
+use crate::schema::SCHEMA_VERSION;
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Manifest {
+    // Changes whenever any of the files do
+    pub version: String,
+    // YYYY-MM-DD the game data was taken
+    pub data_as_of: String,
+    // Of the data files, see schema.rs. Manifests without one predate it.
+    #[serde(default = "first_schema")]
+    pub schema_version: u32,
+    #[serde(default)]
+    pub files: Vec<String>,
+}
+
+impl Manifest {
+    // The version is a hash over the named files' contents, in order
+    pub fn for_files(files: &[(&str, &[u8])], data_as_of: String) -> Self {
+        // FNV-1a, stable across builds and platforms unlike DefaultHasher
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for (name, bytes) in files {
+            for b in name.as_bytes().iter().chain(bytes.iter()) {
+                hash ^= *b as u64;
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+        Self {
+            version: format!("{:016x}", hash),
+            data_as_of,
+            schema_version: SCHEMA_VERSION,
+            files: files.iter().map(|(name, _)| name.to_string()).collect(),
+        }
+    }
+}
+
+fn first_schema() -> u32 {
+    1
+}
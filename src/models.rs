@@ -1,15 +1,25 @@
-use serde_derive::Deserialize;
+use serde_derive::{Deserialize, Serialize};
 use yew::html::ImplicitClone;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 
-#[derive(PartialEq, Clone, Debug)]
+use crate::dataset::Dataset;
+use crate::graph::jump_distances;
+use crate::schema::UnknownFields;
+use crate::shared::Shared;
+use crate::stats::{all_resource_stats, ResourceStats};
+use crate::validate::{has_errors, validate, Issue};
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 pub struct Diagnostics {
     pub planets_with_env: usize,
     pub planets_with_res: usize,
     //pub planets_with_env_res: filter_hits.len()
     pub stars_with_planets_with_env_res: usize,
-    pub filter_hits: Vec<(Planet, Resource)>,
+    // Indexes into Universe::planets and Universe::resources, best factor
+    // first. The app's records are the filter worker's, sent back once it
+    // has built them, so the indexes hold on both sides. See Universe::hits.
+    pub filter_hits: Vec<(usize, usize)>,
 }
 
 impl Diagnostics {
@@ -26,22 +36,28 @@ impl Diagnostics {
     }
 }
 
+impl Default for Diagnostics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[allow(dead_code)]
-#[derive(Deserialize, PartialEq, Clone, Debug)]
-#[serde(deny_unknown_fields, rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
 pub struct Resource {
     pub planet: String,
     pub ticker: String,
-    #[serde(rename(deserialize="type"))]
+    #[serde(rename="type")]
     pub typ: String,
     pub factor: f64,
-    #[serde(skip_deserializing)]
-    pub filtered: bool,
+    #[serde(flatten, skip_serializing)]
+    pub unknown: UnknownFields,
 }
 
 #[allow(dead_code)]
-#[derive(Deserialize, PartialEq, Clone, Debug)]
-#[serde(deny_unknown_fields, rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
 pub struct Planet {
     pub sys_id: String,
     pub name: String,
@@ -51,12 +67,41 @@ pub struct Planet {
     pub temp: f64,
     pub pressure: f64,
     pub fertility: f64,
-    #[serde(skip_deserializing)]
-    pub filtered: bool,
+    // Schema 2 on, see schema.rs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub orbit_index: Option<u32>,
+    // eg. localMarket, warehouse
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub infrastructure: Vec<String>,
+    #[serde(flatten, skip_serializing)]
+    pub unknown: UnknownFields,
 }
 
 impl ImplicitClone for Planet{}
 
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+pub struct MaterialAmount {
+    pub ticker: String,
+    pub amount: u32,
+}
+
+// materials.json: the catalogue entry for a ticker
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Material {
+    pub ticker: String,
+    pub name: String,
+    // eg. ores, gases, construction prefabs
+    pub category: String,
+    // Per unit, in t and m³
+    pub weight: f64,
+    pub volume: f64,
+}
+
+// base.json: the materials for a core module plus those needed for the
+// planet's surface and any extreme environment
+pub type BaseMaterials = Vec<(String, Vec<MaterialAmount>)>;
+
 const GRAVITY_MIN: f64 = 0.25;
 const GRAVITY_MAX: f64 = 2.5;
 const TEMP_MIN: f64 = -25.0;
@@ -65,9 +110,34 @@ const PRESSURE_MIN: f64 = 0.25;
 const PRESSURE_MAX: f64 = 2.0;
 
 impl Planet {
-    pub fn apply_filters(&mut self, filters: &Filters) -> bool {
-        self.filtered = false;
-        self.filtered = if filters.env_filter {
+    pub fn is_fertile(&self) -> bool {
+        self.fertility > -1.0
+    }
+    pub fn fertility_text(&self) -> String {
+        if self.is_fertile() {
+            format!("{:+.0}%", self.fertility * 100.0)
+        } else {
+            "-".to_string()
+        }
+    }
+    pub fn env_value(&self, env: &Environment) -> f64 {
+        match env {
+            Environment::Gravity => self.gravity,
+            Environment::Temp => self.temp,
+            Environment::Pressure => self.pressure,
+        }
+    }
+    // Low, Normal or High against the range that needs no extra materials
+    pub fn env_level(&self, env: &Environment) -> EnvironmentOption {
+        let (min, max) = env.normal_range();
+        match self.env_value(env) {
+            v if v < min => EnvironmentOption::Low,
+            v if v > max => EnvironmentOption::High,
+            _ => EnvironmentOption::Normal,
+        }
+    }
+    pub fn passes_env(&self, filters: &Filters) -> bool {
+        if filters.env_filter {
             (match filters.surface {
                 SurfaceOption::Rocky => self.surface,
                 SurfaceOption::Gaseous => !self.surface,
@@ -82,8 +152,7 @@ impl Planet {
 
         } else {
             true
-        };
-        self.filtered
+        }
     }
     fn check_extremes(
         &self,
@@ -99,35 +168,75 @@ impl Planet {
             EnvironmentOption::High => value >
                 if inc_normal { min } else { max },
             EnvironmentOption::Normal => min <= value && value <= max,
+            EnvironmentOption::Range(lo, hi) => *lo <= value && value <= *hi,
             EnvironmentOption::Ignore => true,
         }
     }
 }
 
-#[derive(Deserialize, PartialEq, Eq, Clone, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
 pub struct Connection {
     pub connection: String,
 }
 
 #[allow(dead_code)]
-#[derive(Deserialize, PartialEq, Clone, Debug)]
-#[serde(deny_unknown_fields, rename_all = "camelCase")]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
 pub struct Star {
     pub sys_id: String,
     pub name: String,
     pub nat_id: String,
-    #[serde(rename(deserialize="type"))]
+    #[serde(rename="type")]
     pub typ: String,
     pub x: f64,
     pub y: f64,
     pub z: f64,
     pub connections: Vec<Connection>,
-    #[serde(skip_deserializing)]
-    pub res_factor: f64,
+    #[serde(flatten, skip_serializing)]
+    pub unknown: UnknownFields,
 }
 
 impl ImplicitClone for Star{}
 
+pub struct Cx {
+    pub code: &'static str,
+    pub nat_id: &'static str,
+}
+
+pub const CX_LIST: [Cx; 4] = [
+    Cx { code: "ANT", nat_id: "ZV-307" },
+    Cx { code: "BEN", nat_id: "UV-351" },
+    Cx { code: "HRT", nat_id: "VH-331" },
+    Cx { code: "MOR", nat_id: "OT-580" },
+];
+
+// The nearest CX to a system by jumps and by straight line distance, as
+// (code, jumps) and (code, map units). None when no CX is reachable.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(from = "CxDistanceData")]
+pub struct CxDistance {
+    pub jumps: Option<(&'static str, u32)>,
+    pub distance: Option<(&'static str, f64)>,
+}
+
+// A CxDistance as it comes from the filter worker, the codes not yet
+// matched back to CX_LIST
+#[derive(Deserialize)]
+struct CxDistanceData {
+    jumps: Option<(String, u32)>,
+    distance: Option<(String, f64)>,
+}
+
+impl From<CxDistanceData> for CxDistance {
+    fn from(data: CxDistanceData) -> Self {
+        let code = |code: &str| CX_LIST.iter().find(|cx| cx.code == code).map(|cx| cx.code);
+        Self {
+            jumps: data.jumps.and_then(|(c, jumps)| Some((code(&c)?, jumps))),
+            distance: data.distance.and_then(|(c, distance)| Some((code(&c)?, distance))),
+        }
+    }
+}
+
 impl Star {
     pub fn new() -> Self {
         Self {
@@ -139,41 +248,81 @@ impl Star {
             y: 0.0,
             z: 0.0,
             connections: Vec::new(),
-            res_factor: 0.0,
+            unknown: UnknownFields::default(),
         }
     }
+    // Straight line distance in map units
+    pub fn distance_to(&self, other: &Star) -> f64 {
+        ((self.x - other.x).powi(2) + (self.y - other.y).powi(2) + (self.z - other.z).powi(2)).sqrt()
+    }
+}
+
+impl Default for Star {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 // TODO sort out what should be in Universe and what should be in PrUnApp
 #[derive(PartialEq, Clone, Debug)]
 pub struct Universe {
     pub stars: Vec<Star>,
-    pub selected_star: Option<Star>,
     pub planets: Vec<Planet>,
     pub resources: Vec<Resource>,
     pub star_list: Vec<String>,
     pub res_list: Vec<String>,
-    pub selected_res: Option<String>,
     pub res_max_factor: HashMap<String, f64>,
-    pub diagnostics: Diagnostics,
+    pub res_factors: HashMap<String, Vec<f64>>,
+    pub res_stats: HashMap<String, ResourceStats>,
+    // By sys_id
+    pub cx_distances: HashMap<String, CxDistance>,
+    pub data_issues: Vec<Issue>,
+    pub base_materials: BaseMaterials,
+    // By ticker
+    pub materials: HashMap<String, Material>,
 }
 
-impl ImplicitClone for Universe {}
-
 impl Universe {
     pub fn new() -> Self {
         Self {
             stars: Vec::new(),
-            selected_star: None,
             planets: Vec::new(),
             resources: Vec::new(),
             star_list: Vec::new(),
             res_list: Vec::new(),
-            selected_res: None,
             res_max_factor: HashMap::new(),
-            diagnostics: Diagnostics::new(),
+            res_factors: HashMap::new(),
+            res_stats: HashMap::new(),
+            cx_distances: HashMap::new(),
+            data_issues: Vec::new(),
+            base_materials: Vec::new(),
+            materials: HashMap::new(),
+        }
+    }
+    // Everything at once, as the tools outside the browser have it. The
+    // star coordinates are left as in the files.
+    pub fn from_dataset(data: Dataset) -> Self {
+        let mut universe = Self::new();
+        universe.stars = data.stars;
+        universe.planets = data.planets;
+        universe.resources = data.resources;
+        universe.build();
+        universe
+    }
+    // A copy of the records alone, eg. to pack
+    pub fn dataset(&self) -> Dataset {
+        Dataset {
+            stars: self.stars.clone(),
+            planets: self.planets.clone(),
+            resources: self.resources.clone(),
         }
     }
+    // Clean the loaded data then work out everything derived from it
+    pub fn build(&mut self) {
+        self.check_data();
+        self.create_index();
+        self.create_cx_distances();
+    }
     pub fn fix_star_y(&mut self) {
         for star in &mut self.stars {
             star.y *= -1.0;
@@ -201,6 +350,13 @@ impl Universe {
             };
 
             self.res_max_factor.insert(ticker.to_string(), factor.max(resource.factor));
+            self.res_factors
+                .entry(ticker.to_string())
+                .or_default()
+                .push(resource.factor);
+        }
+        for factors in self.res_factors.values_mut() {
+            factors.sort_by(|a, b| a.partial_cmp(b).unwrap());
         }
         self.res_list = self.res_max_factor
             .keys()
@@ -209,100 +365,329 @@ impl Universe {
         self.res_list.sort();
         self.res_list.insert(0, "-None-".to_string());
     }
-    // TODO Error checks to be added
-    pub fn star_from_name(&self, name: String) -> Option<Star> {
-        let name = name.to_ascii_uppercase();
-        match name.chars().nth(2).unwrap() {
-            '-' => {
-                let text = &name[..6];
-                let selected_star = self.stars.iter()
-                    .find(|s| s.nat_id == text)
-                    .unwrap()
-                    .clone();
-                Some(selected_star)
+    // Once everything has loaded. Records that would leave a dangling
+    // reference are dropped, so the index wants building after this.
+    pub fn check_data(&mut self) {
+        self.data_issues = validate(&self.stars, &self.planets, &self.resources);
+        if has_errors(&self.data_issues) {
+            let star_ids: HashSet<String> = self.stars.iter().map(|s| s.sys_id.clone()).collect();
+            for star in &mut self.stars {
+                star.connections.retain(|c| star_ids.contains(&c.connection));
             }
-            _ => {
-                let planet = self.planets.iter().find(|p| {
-                    p.name.to_ascii_uppercase() == name
-                });
-                if let Some(planet) = planet {
-                    let selected_star = self.stars.iter()
-                        .find(|s| s.sys_id == planet.sys_id)
-                        .unwrap()
-                        .clone();
-                    Some(selected_star)
-                } else {
-                    let selected_star = self.stars.iter().find(|s| {
-                        s.name.to_ascii_uppercase() == name
-                    }).unwrap().clone();
-                    Some(selected_star)
+            self.planets.retain(|p| star_ids.contains(&p.sys_id));
+            let planet_ids: HashSet<String> = self.planets.iter().map(|p| p.nat_id.clone()).collect();
+            self.resources.retain(|r| planet_ids.contains(&r.planet));
+        }
+        // The factors get sorted, which a NaN would panic
+        self.resources.retain(|r| r.factor.is_finite());
+    }
+    // The lookups built from the full data. Slow enough that the app leaves
+    // them to the filter worker and takes a copy.
+    pub fn create_index(&mut self) {
+        self.star_list.clear();
+        self.res_max_factor.clear();
+        self.res_factors.clear();
+        self.create_star_list();
+        self.create_resource_data();
+        self.create_resource_stats();
+    }
+    pub fn index(&self) -> UniverseIndex {
+        UniverseIndex {
+            star_list: self.star_list.clone(),
+            res_list: self.res_list.clone(),
+            res_max_factor: self.res_max_factor.clone(),
+            res_factors: self.res_factors.clone(),
+            res_stats: self.res_stats.clone(),
+        }
+    }
+    pub fn set_index(&mut self, index: UniverseIndex) {
+        self.star_list = index.star_list;
+        self.res_list = index.res_list;
+        self.res_max_factor = index.res_max_factor;
+        self.res_factors = index.res_factors;
+        self.res_stats = index.res_stats;
+    }
+    // Needs both planets and resources, so waits until both have loaded
+    pub fn create_resource_stats(&mut self) {
+        if self.planets.is_empty() || self.resources.is_empty() {
+            return;
+        }
+        let tickers: Vec<String> = self.res_max_factor.keys().cloned().collect();
+        self.res_stats = all_resource_stats(&tickers, &self.resources, &self.planets);
+    }
+    // The share of planets with the ticker at or below the factor, 0-100
+    pub fn res_percentile(&self, ticker: &str, factor: f64) -> f64 {
+        match self.res_factors.get(ticker) {
+            Some(factors) if !factors.is_empty() => {
+                let below = factors.partition_point(|&f| f <= factor);
+                below as f64 / factors.len() as f64 * 100.0
+            }
+            _ => 0.0,
+        }
+    }
+    // How good a factor is for its ticker, 0-1, by the chosen normalisation
+    pub fn concentration(&self, ticker: &str, factor: f64, normalisation: Normalisation) -> f64 {
+        match normalisation {
+            Normalisation::PercentOfMax => match self.res_max_factor.get(ticker) {
+                Some(&max) if max > 0.0 => factor / max,
+                _ => 0.0,
+            },
+            Normalisation::Percentile => self.res_percentile(ticker, factor) / 100.0,
+        }
+    }
+    // The planet and resource pairs the filters hit, best factor first
+    pub fn hits<'a>(&'a self, results: &'a FilterResults) -> impl Iterator<Item = (&'a Planet, &'a Resource)> {
+        results.diagnostics.filter_hits
+            .iter()
+            .filter_map(move |&(p, r)| Some((self.planets.get(p)?, self.resources.get(r)?)))
+    }
+    pub fn planet_from_id(&self, nat_id: &str) -> Option<&Planet> {
+        self.planets.iter().find(|p| p.nat_id == nat_id)
+    }
+    // A planet by natural id or name, ignoring case
+    pub fn planet_from_name(&self, name: &str) -> Option<&Planet> {
+        let name = name.trim().to_ascii_uppercase();
+        self.planets.iter().find(|p| {
+            p.nat_id.to_ascii_uppercase() == name || p.name.to_ascii_uppercase() == name
+        })
+    }
+    // A system by natural id or name, ignoring case
+    pub fn find_star(&self, name: &str) -> Option<&Star> {
+        let name = name.trim().to_ascii_uppercase();
+        self.stars.iter().find(|s| {
+            s.nat_id.to_ascii_uppercase() == name || s.name.to_ascii_uppercase() == name
+        })
+    }
+    pub fn star_for_planet(&self, p: &Planet) -> Option<&Star> {
+        self.stars.iter().find(|s| s.sys_id == p.sys_id)
+    }
+    pub fn neighbours(&self, star: &Star) -> Vec<&Star> {
+        star.connections
+            .iter()
+            .filter_map(|c| self.stars.iter().find(|s| s.sys_id == c.connection))
+            .collect()
+    }
+    // One search out from each CX rather than one per system
+    pub fn create_cx_distances(&mut self) {
+        let cx_stars: Vec<(&'static str, &Star, HashMap<String, u32>)> = CX_LIST
+            .iter()
+            .filter_map(|cx| {
+                let star = self.stars.iter().find(|s| s.nat_id == cx.nat_id)?;
+                Some((cx.code, star, jump_distances(&self.stars, &star.sys_id)))
+            })
+            .collect();
+        self.cx_distances = self.stars
+            .iter()
+            .map(|s| {
+                let jumps = cx_stars
+                    .iter()
+                    .filter_map(|(code, _, jumps)| jumps.get(&s.sys_id).map(|&j| (*code, j)))
+                    .min_by_key(|&(_, j)| j);
+                let distance = cx_stars
+                    .iter()
+                    .map(|(code, cx, _)| (*code, s.distance_to(cx)))
+                    .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+                (s.sys_id.clone(), CxDistance { jumps, distance })
+            })
+            .collect();
+    }
+    // The closest CX by jumps, as (code, jumps)
+    pub fn nearest_cx(&self, star: &Star) -> Option<(&'static str, u32)> {
+        self.cx_distances.get(&star.sys_id).and_then(|d| d.jumps)
+    }
+    // The materials to build a base on the planet, in base.json order
+    pub fn base_bill(&self, p: &Planet) -> Vec<MaterialAmount> {
+        let mut parts = vec!["base", if p.surface { "rocky" } else { "gaseous" }];
+        let envs = [
+            (Environment::Gravity, "lowGravity", "highGravity"),
+            (Environment::Temp, "lowTemp", "highTemp"),
+            (Environment::Pressure, "lowPressure", "highPressure"),
+        ];
+        for (env, low, high) in envs.iter() {
+            match p.env_level(env) {
+                EnvironmentOption::Low => parts.push(low),
+                EnvironmentOption::High => parts.push(high),
+                _ => (),
+            }
+        }
+        let mut bill: Vec<MaterialAmount> = Vec::new();
+        for (part, materials) in &self.base_materials {
+            if !parts.contains(&part.as_str()) {
+                continue;
+            }
+            for m in materials {
+                match bill.iter_mut().find(|b| b.ticker == m.ticker) {
+                    Some(b) => b.amount += m.amount,
+                    None => bill.push(m.clone()),
                 }
             }
         }
+        bill
     }
-    pub fn apply_filters(&mut self, filters: &Filters) {
-
-        self.diagnostics = Diagnostics::new();
-
-        for planet in &mut self.planets {
-            if planet.apply_filters(filters) {
-                self.diagnostics.planets_with_env += 1;
+    // The catalogue name for a ticker, the ticker itself if it isn't there
+    pub fn material_name<'a>(&'a self, ticker: &'a str) -> &'a str {
+        self.materials.get(ticker).map_or(ticker, |m| m.name.as_str())
+    }
+    // Total weight (t) and volume (m³) of a bill, None if any of its
+    // materials isn't in the catalogue
+    pub fn bill_load(&self, bill: &[MaterialAmount]) -> Option<(f64, f64)> {
+        bill.iter().try_fold((0.0, 0.0), |(weight, volume), m| {
+            let material = self.materials.get(&m.ticker)?;
+            let amount = m.amount as f64;
+            Some((weight + material.weight * amount, volume + material.volume * amount))
+        })
+    }
+    // The resource list by catalogue category, as (category, [(ticker,
+    // name)]). Tickers missing from the catalogue go under Other.
+    pub fn res_groups(&self) -> Vec<(String, Vec<(String, String)>)> {
+        let mut groups: Vec<(String, Vec<(String, String)>)> = Vec::new();
+        for ticker in self.res_list.iter().filter(|t| self.res_max_factor.contains_key(*t)) {
+            let (category, name) = match self.materials.get(ticker) {
+                Some(m) => (m.category.clone(), m.name.clone()),
+                None => ("other".to_string(), ticker.clone()),
+            };
+            match groups.iter_mut().find(|(c, _)| *c == category) {
+                Some((_, tickers)) => tickers.push((ticker.clone(), name)),
+                None => groups.push((category, vec![(ticker.clone(), name)])),
             }
         }
+        groups.sort_by(|a, b| (a.0 == "other", &a.0).cmp(&(b.0 == "other", &b.0)));
+        groups
+    }
+    // A system by CX code, natural id or name
+    pub fn find_system(&self, name: &str) -> Option<&Star> {
+        let name = name.to_ascii_uppercase();
+        let nat_id = match CX_LIST.iter().find(|cx| cx.code == name) {
+            Some(cx) => cx.nat_id.to_string(),
+            None => name,
+        };
+        self.stars.iter().find(|s| {
+            s.nat_id == nat_id || s.name.to_ascii_uppercase() == nat_id
+        })
+    }
+    // The system searched for, or the one with the planet searched for
+    pub fn find_origin(&self, name: &str) -> Option<&Star> {
+        self.find_system(name.trim())
+            .or_else(|| self.star_for_planet(self.planet_from_name(name)?))
+    }
+    pub fn star_from_name(&self, name: String) -> Option<Star> {
+        self.find_origin(&name).cloned()
+    }
+    // Everything the filters let through, and the selected resource's best
+    // factor in each system for the map
+    pub fn filter(&self, filters: &Filters, selected_res: &Option<String>) -> FilterResults {
+        let mut passing: Vec<bool> = self.planets.iter().map(|p| p.passes_env(filters)).collect();
+        self.apply_res_filters(filters, &mut passing);
+        let reachable = self.reachable_systems(filters);
+        if let Some(reachable) = &reachable {
+            for (pass, planet) in passing.iter_mut().zip(&self.planets) {
+                *pass = *pass && reachable.contains(&planet.sys_id);
+            }
+        }
+        let passed: HashMap<&str, usize> = self.planets
+            .iter()
+            .zip(&passing)
+            .enumerate()
+            .filter(|(_, (_, &pass))| pass)
+            .map(|(i, (p, _))| (p.nat_id.as_str(), i))
+            .collect();
 
-        self.resources
-            .iter_mut()
-            .for_each(|r| r.filtered = false);
-        self.stars
-            .iter_mut()
-            .for_each(|s| s.res_factor = 0.0);
-
-
-        match &self.selected_res {
+        let mut diagnostics = Diagnostics::new();
+        diagnostics.planets_with_env = passed.len();
+        let mut star_factors: HashMap<String, f64> = HashMap::new();
+        match selected_res {
             Some(res) => {
-                for resource in &mut self.resources {
-                    if resource.ticker.eq(res) {
-                        resource.filtered = true;
-                        self.diagnostics.planets_with_res += 1;
-                        let planet = self.planets
-                            .iter()
-                            .find(|p| p.nat_id.eq(&resource.planet))
-                            .unwrap();
-                        if planet.filtered {
-                            let star = self.stars
-                                .iter_mut()
-                                .find(|s| s.sys_id.eq(&planet.sys_id))
-                                .unwrap();
-                            star.res_factor = star.res_factor.max(resource.factor);
-                            self.diagnostics.filter_hits.push((planet.clone(), resource.clone()));
-                        }
+                for (r, resource) in self.resources.iter().enumerate().filter(|(_, r)| r.ticker.eq(res)) {
+                    diagnostics.planets_with_res += 1;
+                    if let Some(&p) = passed.get(resource.planet.as_str()) {
+                        let factor = star_factors.entry(self.planets[p].sys_id.clone()).or_insert(0.0);
+                        *factor = factor.max(resource.factor);
+                        diagnostics.filter_hits.push((p, r));
                     }
                 }
-                self.diagnostics.filter_hits.sort_by(
-                    |a, b| b.1.factor.partial_cmp(&a.1.factor).unwrap());
+                let resources = &self.resources;
+                diagnostics.filter_hits.sort_by(
+                    |a, b| resources[b.1].factor.partial_cmp(&resources[a.1].factor).unwrap());
             }
             None => {
-                for planet in self.planets.iter() {
-                    if planet.filtered {
-                        let star = self.stars
-                            .iter_mut()
-                            .find(|s| s.sys_id.eq(&planet.sys_id))
-                            .unwrap();
-                        star.res_factor = 1.0;
-                    }
+                for &p in passed.values() {
+                    star_factors.insert(self.planets[p].sys_id.clone(), 1.0);
                 }
             }
         }
-        self.diagnostics.stars_with_planets_with_env_res = self.stars_with_planets_env_res();
+        diagnostics.stars_with_planets_with_env_res = star_factors.values().filter(|&&f| f > 0.0).count();
+
+        FilterResults {
+            planets: passed.keys().map(|nat_id| nat_id.to_string()).collect(),
+            star_factors,
+            reachable,
+            diagnostics,
+        }
     }
-    pub fn stars_with_planets_env_res(&self) -> usize {
-        self.stars
-            .iter()
-            .filter(|s| s.res_factor.gt(&0.0))
-            .count()
+    fn apply_res_filters(&self, filters: &Filters, passing: &mut [bool]) {
+        if filters.res_filters.is_empty() {
+            return;
+        }
+        let mut factors: HashMap<(&str, &str), f64> = HashMap::new();
+        for r in &self.resources {
+            factors.insert((&r.planet, &r.ticker), r.factor);
+        }
+        let res_max_factor = &self.res_max_factor;
+        for (pass, planet) in passing.iter_mut().zip(&self.planets).filter(|(pass, _)| **pass) {
+            *pass = filters.res_filters.iter().all(|f| {
+                // A planet without the resource counts as a factor of 0
+                let factor = factors
+                    .get(&(planet.nat_id.as_str(), f.ticker.as_str()))
+                    .copied()
+                    .unwrap_or(0.0);
+                let value = if f.percent {
+                    match res_max_factor.get(&f.ticker) {
+                        Some(max) if *max > 0.0 => factor / max * 100.0,
+                        _ => 0.0,
+                    }
+                } else {
+                    factor
+                };
+                f.cmp.test(value, f.value)
+            });
+        }
+    }
+    // The sys_ids passing every jump and radius filter, None when there
+    // are no such filters
+    fn reachable_systems(&self, filters: &Filters) -> Option<HashSet<String>> {
+        if filters.jump_filters.is_empty() && filters.radius_filters.is_empty() {
+            return None;
+        }
+        let mut reachable: HashSet<String> = self.stars.iter().map(|s| s.sys_id.clone()).collect();
+        for f in &filters.jump_filters {
+            // CX on its own means whichever CX is nearest. An origin that
+            // isn't in the data matches nothing.
+            let jumps = if f.origin.eq_ignore_ascii_case("CX") {
+                self.cx_distances
+                    .iter()
+                    .filter_map(|(sys_id, d)| d.jumps.map(|(_, j)| (sys_id.clone(), j)))
+                    .collect()
+            } else {
+                match self.find_origin(&f.origin) {
+                    Some(origin) => jump_distances(&self.stars, &origin.sys_id),
+                    None => HashMap::new(),
+                }
+            };
+            reachable.retain(|sys_id| {
+                jumps.get(sys_id).is_some_and(|&j| f.cmp.test(j as f64, f.jumps as f64))
+            });
+        }
+        let stars: HashMap<&str, &Star> = self.stars.iter().map(|s| (s.sys_id.as_str(), s)).collect();
+        for f in &filters.radius_filters {
+            let origin = self.find_origin(&f.origin);
+            reachable.retain(|sys_id| match (origin, stars.get(sys_id.as_str())) {
+                (Some(origin), Some(star)) => f.cmp.test(origin.distance_to(star), f.radius),
+                _ => false,
+            });
+        }
+        Some(reachable)
     }
-    pub fn planets_for_selected_star(&self) -> Vec<Planet> {
-        let star = self.selected_star.as_ref().unwrap();
+    pub fn planets_for_star(&self, star: &Star) -> Vec<Planet> {
         self.planets
             .iter()
             .filter(|p| p.sys_id.eq(&star.sys_id))
@@ -324,11 +709,80 @@ impl Default for Universe {
     }
 }
 
+// What Universe::create_index builds
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Default)]
+pub struct UniverseIndex {
+    pub star_list: Vec<String>,
+    pub res_list: Vec<String>,
+    pub res_max_factor: HashMap<String, f64>,
+    pub res_factors: HashMap<String, Vec<f64>>,
+    pub res_stats: HashMap<String, ResourceStats>,
+}
+
+// What the filters let through, rebuilt whole whenever they change. Only
+// ids and indexes, so it's cheap to send back from the filter worker.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Default)]
+pub struct FilterResults {
+    // Planet nat_ids
+    pub planets: HashSet<String>,
+    // By sys_id, the best factor of the selected resource on a planet let
+    // through, or 1.0 for any such planet when there's no resource
+    pub star_factors: HashMap<String, f64>,
+    // sys_ids within the jump and radius filters, None when unlimited
+    pub reachable: Option<HashSet<String>>,
+    pub diagnostics: Diagnostics,
+}
+
+// What's selected and what the filters let through. Kept apart from the
+// Universe, which only changes as the data loads, so it can be shared
+// with every component rather than copied into each.
+#[derive(PartialEq, Clone, Debug)]
+pub struct View {
+    pub selected_star: Option<Star>,
+    pub selected_res: Option<String>,
+    pub normalisation: Normalisation,
+    pub results: Shared<FilterResults>,
+}
+
+impl ImplicitClone for View {}
+
+impl View {
+    pub fn new() -> Self {
+        Self {
+            selected_star: None,
+            selected_res: None,
+            normalisation: Normalisation::PercentOfMax,
+            results: Shared::default(),
+        }
+    }
+    pub fn is_filtered(&self, p: &Planet) -> bool {
+        self.results.planets.contains(&p.nat_id)
+    }
+    pub fn star_factor(&self, star: &Star) -> f64 {
+        self.results.star_factors.get(&star.sys_id).copied().unwrap_or(0.0)
+    }
+}
+
+impl Default for View {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 pub enum Toggle {
     ShowCx,
     ShowRoutes,
     IncEnvFilter,
-    IncNormal
+    IncNormal,
+    Percentile,
+}
+
+// One outlier makes every other planet look poor against the best, so
+// concentration can be ranked within the ticker's distribution instead
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Normalisation {
+    PercentOfMax,
+    Percentile,
 }
 
 pub type ScaleOptions = HashMap<String, (f64, bool)>;
@@ -357,10 +811,19 @@ impl MapFeatures {
     }
     pub fn set_selected_scale(&mut self, selected: f64) {
         self.selected_scale = selected;
+        for option in self.scale_options.values_mut() {
+            option.1 = (option.0 - selected).abs() < f64::EPSILON;
+        }
     }
 }
 
-#[derive(PartialEq, Eq, Clone, Debug)]
+impl Default for MapFeatures {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
 pub enum SurfaceOption {
     Rocky,
     Gaseous,
@@ -388,17 +851,31 @@ pub enum Environment {
     Pressure,
 }
 
-#[derive(PartialEq, Eq, Clone, Debug)]
+impl Environment {
+    pub fn normal_range(&self) -> (f64, f64) {
+        match self {
+            Environment::Gravity => (GRAVITY_MIN, GRAVITY_MAX),
+            Environment::Temp => (TEMP_MIN, TEMP_MAX),
+            Environment::Pressure => (PRESSURE_MIN, PRESSURE_MAX),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 pub enum EnvironmentOption {
     Normal,
     Low,
     High,
     Ignore,
+    Range(f64, f64),
 }
 
 impl fmt::Display for EnvironmentOption {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", self)
+        match self {
+            EnvironmentOption::Range(lo, hi) => write!(f, "{}..{}", lo, hi),
+            _ => write!(f, "{:?}", self),
+        }
     }
 }
 
@@ -407,11 +884,79 @@ pub fn to_env_option(s: &str) -> EnvironmentOption {
         "Low" => EnvironmentOption::Low,
         "Normal" => EnvironmentOption::Normal,
         "High" => EnvironmentOption::High,
-        _ => EnvironmentOption::Ignore,
+        _ => {
+            let mut bounds = s.splitn(2, "..").map(|b| b.parse::<f64>());
+            match (bounds.next(), bounds.next()) {
+                (Some(Ok(lo)), Some(Ok(hi))) => EnvironmentOption::Range(lo, hi),
+                _ => EnvironmentOption::Ignore,
+            }
+        }
     }
 }
 
-#[derive(PartialEq, Clone, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Comparison {
+    Lt,
+    Le,
+    Eq,
+    Ge,
+    Gt,
+}
+
+impl Comparison {
+    pub fn test(&self, value: f64, limit: f64) -> bool {
+        match self {
+            Comparison::Lt => value < limit,
+            Comparison::Le => value <= limit,
+            Comparison::Eq => (value - limit).abs() < f64::EPSILON,
+            Comparison::Ge => value >= limit,
+            Comparison::Gt => value > limit,
+        }
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Comparison::Lt => "<",
+            Comparison::Le => "<=",
+            Comparison::Eq => "=",
+            Comparison::Ge => ">=",
+            Comparison::Gt => ">",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+// eg. FEO>0.3 or LST>40% (percent of the best planet's factor)
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct ResFilter {
+    pub ticker: String,
+    pub cmp: Comparison,
+    pub value: f64,
+    pub percent: bool,
+}
+
+// eg. jumps(ANT)<=5, the origin being a CX code, a system, a planet
+// standing for its system or CX for any
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct JumpFilter {
+    pub origin: String,
+    pub cmp: Comparison,
+    pub jumps: u32,
+}
+
+// eg. dist(AX-383)<=300, straight line distance in map units from a system
+// or a planet's system
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct RadiusFilter {
+    pub origin: String,
+    pub cmp: Comparison,
+    pub radius: f64,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(default)]
 pub struct Filters {
     pub env_filter: bool,
     pub inc_normal: bool,
@@ -419,6 +964,9 @@ pub struct Filters {
     pub gravity: EnvironmentOption,
     pub temp: EnvironmentOption,
     pub pressure: EnvironmentOption,
+    pub res_filters: Vec<ResFilter>,
+    pub jump_filters: Vec<JumpFilter>,
+    pub radius_filters: Vec<RadiusFilter>,
     pub stars_filter_map: HashMap<String, f64>
 }
 
@@ -431,11 +979,20 @@ impl Filters {
             gravity: EnvironmentOption::Normal,
             temp: EnvironmentOption::Normal,
             pressure: EnvironmentOption::Normal,
+            res_filters: Vec::new(),
+            jump_filters: Vec::new(),
+            radius_filters: Vec::new(),
             stars_filter_map: HashMap::new(),
         }
     }
 }
 
+impl Default for Filters {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ImplicitClone for Filters{}
 
 #[allow(dead_code)]
@@ -444,3 +1001,110 @@ pub enum Position {
     R,
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STARS: &str = r#"[
+        {"sysId": "s1", "name": "Antares I", "natId": "ZV-307", "type": "M",
+         "x": 0.0, "y": 0.0, "z": 0.0, "connections": [{"connection": "s2"}, {"connection": "s9"}]},
+        {"sysId": "s2", "name": "Hortus", "natId": "ZV-639", "type": "G",
+         "x": 0.0, "y": 0.0, "z": 0.0, "connections": [{"connection": "s1"}]}
+    ]"#;
+    const PLANETS: &str = r#"[
+        {"sysId": "s2", "name": "Montem", "natId": "ZV-639a", "surface": true,
+         "gravity": 1.0, "temp": 20.0, "pressure": 1.0, "fertility": 0.0},
+        {"sysId": "s9", "name": "Lost", "natId": "ZZ-999a", "surface": true,
+         "gravity": 1.0, "temp": 20.0, "pressure": 1.0, "fertility": 0.0}
+    ]"#;
+
+    fn universe() -> Universe {
+        let mut universe = Universe::new();
+        universe.stars = serde_json::from_str(STARS).unwrap();
+        universe.planets = serde_json::from_str(PLANETS).unwrap();
+        universe.resources = vec![
+            resource("ZV-639a", "FEO", 0.3),
+            resource("ZV-639a", "H2O", f64::NAN),
+            resource("ZZ-999a", "FEO", 0.2),
+            resource("ZZ-999b", "LST", 0.1),
+        ];
+        universe
+    }
+
+    fn resource(planet: &str, ticker: &str, factor: f64) -> Resource {
+        Resource {
+            planet: planet.to_string(),
+            ticker: ticker.to_string(),
+            typ: "MINERAL".to_string(),
+            factor,
+            unknown: Default::default(),
+        }
+    }
+
+    #[test]
+    fn check_data_drops_dangling_records() {
+        let mut universe = universe();
+        universe.check_data();
+        assert!(has_errors(&universe.data_issues));
+        assert_eq!(universe.stars[0].connections, [Connection { connection: "s2".to_string() }]);
+        let planets: Vec<&str> = universe.planets.iter().map(|p| p.nat_id.as_str()).collect();
+        assert_eq!(planets, ["ZV-639a"]);
+        let resources: Vec<(&str, &str)> = universe.resources
+            .iter()
+            .map(|r| (r.planet.as_str(), r.ticker.as_str()))
+            .collect();
+        assert_eq!(resources, [("ZV-639a", "FEO")]);
+    }
+
+    #[test]
+    fn check_data_always_drops_non_finite_factors() {
+        let mut universe = universe();
+        universe.stars[0].connections.pop();
+        universe.planets.pop();
+        universe.resources.truncate(2);
+        universe.check_data();
+        assert!(!has_errors(&universe.data_issues));
+        assert_eq!(universe.resources.len(), 1);
+        assert_eq!(universe.resources[0].ticker, "FEO");
+    }
+
+    #[test]
+    fn build_survives_what_check_data_drops() {
+        let mut universe = universe();
+        universe.build();
+        let tickers: Vec<&String> = universe.res_max_factor.keys().collect();
+        assert_eq!(tickers, ["FEO"]);
+        assert_eq!(universe.nearest_cx(&universe.stars[1]), Some(("ANT", 1)));
+    }
+
+    #[test]
+    fn star_from_name_misses_quietly() {
+        let universe = universe();
+        let found = |name: &str| universe.star_from_name(name.to_string()).map(|s| s.nat_id);
+        assert_eq!(found("ant"), Some("ZV-307".to_string()));
+        assert_eq!(found("hortus"), Some("ZV-639".to_string()));
+        assert_eq!(found("ZV-639a"), Some("ZV-639".to_string()));
+        assert_eq!(found("Montem"), Some("ZV-639".to_string()));
+        assert_eq!(found("Lost"), None);
+        assert_eq!(found("ZZ"), None);
+        assert_eq!(found(""), None);
+    }
+
+    #[test]
+    fn reach_origins_can_be_planets() {
+        let mut universe = universe();
+        universe.build();
+        let reach = |query: &str| {
+            let filters = crate::query::parse_unchecked(query).unwrap().filters;
+            let mut systems: Vec<String> =
+                universe.reachable_systems(&filters).unwrap().into_iter().collect();
+            systems.sort();
+            systems
+        };
+        assert_eq!(reach("jumps(Montem)<=0"), ["s2"]);
+        assert_eq!(reach("jumps(zv-639a)<=1"), ["s1", "s2"]);
+        assert_eq!(reach("dist(Montem)<=0"), ["s1", "s2"]);
+        assert!(reach("jumps(Nowhere)<=5").is_empty());
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use yew::prelude::*;
+
+use crate::bases::BaseAction;
+use crate::graph::jump_distances;
+use crate::models::{Environment, EnvironmentOption, Planet, Universe};
+use crate::shared::Shared;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    pub universe: Shared<Universe>,
+    pub bases: Vec<String>,
+    pub base_signal: Callback<BaseAction>,
+    pub search_star_signal: Callback<String>,
+}
+
+pub enum Msg {
+    Name(String),
+    Add,
+    Remove(String),
+    Select(String),
+}
+
+#[allow(dead_code)]
+pub struct MyBases {
+    link: ComponentLink<Self>,
+    props: Props,
+    name: String,
+    error: Option<String>,
+}
+
+impl MyBases {
+    fn view_base(&self, p: &Planet) -> Html {
+        let universe = &self.props.universe;
+        let system = universe.star_for_planet(p);
+        let cx = match system.and_then(|s| universe.nearest_cx(s)) {
+            Some((code, jumps)) => format!("{} {} jumps", code, jumps),
+            None => "No CX reachable".to_string(),
+        };
+        let envs = [
+            (Environment::Gravity, 2),
+            (Environment::Temp, 0),
+            (Environment::Pressure, 2),
+        ];
+        let resources = universe.resources_for_planet(p);
+        let select = p.nat_id.clone();
+        let remove = p.nat_id.clone();
+        html! {
+            <li>
+                <span class="link" onclick=self.link.callback(move |_| Msg::Select(select.clone()))>
+                    {&p.name}
+                </span>
+                {format!(" {}", system.map_or("", |s| s.name.as_str()))}
+                <i
+                    class="fas fa-times remove-base"
+                    onclick=self.link.callback(move |_| Msg::Remove(remove.clone()))
+                />
+                <div>
+                    { for envs.iter().map(|(env, dp)| {
+                        let class = match p.env_level(env) {
+                            EnvironmentOption::Low => "low",
+                            EnvironmentOption::High => "high",
+                            _ => "normal",
+                        };
+                        html! { <span class=class>{format!("{:.*} ", *dp, p.env_value(env))}</span> }
+                    })}
+                    {format!("| {}", cx)}
+                </div>
+                <div>
+                    { for resources.iter().map(|r| html! {
+                        <span>{format!("{} {:.2} ", r.ticker, r.factor)}</span>
+                    })}
+                </div>
+            </li>
+        }
+    }
+
+    // Jumps between every pair of bases
+    fn view_jumps(&self, planets: &[&Planet]) -> Html {
+        let universe = &self.props.universe;
+        if planets.len() < 2 {
+            return html! {};
+        }
+        let distances: HashMap<&str, HashMap<String, u32>> = planets
+            .iter()
+            .map(|p| (p.nat_id.as_str(), jump_distances(&universe.stars, &p.sys_id)))
+            .collect();
+        html! {
+            <table class="base-jumps">
+                <tr>
+                    <th></th>
+                    { for planets.iter().map(|p| html! { <th>{&p.name}</th> }) }
+                </tr>
+                { for planets.iter().map(|from| html! {
+                    <tr>
+                        <td>{&from.name}</td>
+                        { for planets.iter().map(|to| {
+                            let jumps = distances[from.nat_id.as_str()]
+                                .get(&to.sys_id)
+                                .map_or("-".to_string(), |j| j.to_string());
+                            html! { <td>{jumps}</td> }
+                        })}
+                    </tr>
+                })}
+            </table>
+        }
+    }
+}
+
+impl Component for MyBases {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Self {
+            link,
+            props,
+            name: String::new(),
+            error: None,
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> bool {
+        if self.props != props {
+            self.props = props;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Name(name) => {
+                self.name = name;
+                false
+            }
+            Msg::Add => {
+                match self.props.universe.planet_from_name(&self.name) {
+                    Some(p) => {
+                        self.props.base_signal.emit(BaseAction::Add(p.nat_id.clone()));
+                        self.name = String::new();
+                        self.error = None;
+                    }
+                    None => self.error = Some(format!("No planet called {}", self.name.trim())),
+                }
+                true
+            }
+            Msg::Remove(nat_id) => {
+                self.props.base_signal.emit(BaseAction::Remove(nat_id));
+                false
+            }
+            Msg::Select(nat_id) => {
+                self.props.search_star_signal.emit(nat_id);
+                false
+            }
+        }
+    }
+
+    fn view(&self) -> Html {
+        let universe = &self.props.universe;
+        let planets: Vec<&Planet> = self.props.bases
+            .iter()
+            .filter_map(|id| universe.planet_from_id(id))
+            .collect();
+        html! {
+            <div class="my-bases">
+                <h3>{"My bases"}</h3>
+                <div>
+                    <input
+                        type="text"
+                        placeholder="Planet name or id"
+                        value=self.name.clone()
+                        oninput=self.link.callback(|e: InputData| Msg::Name(e.value))
+                    />
+                    <button onclick=self.link.callback(|_| Msg::Add)>{"Add"}</button>
+                </div>
+                { match &self.error {
+                    Some(e) => html! { <div class="error">{e}</div> },
+                    None => html! {},
+                }}
+                <ul>
+                    { for planets.iter().map(|p| self.view_base(p)) }
+                </ul>
+                {self.view_jumps(&planets)}
+            </div>
+        }
+    }
+}
@@ -0,0 +1,349 @@
+// A compact binary form of the dataset, quicker to fetch and decode in the
+// browser than the JSON. Little endian throughout:
+//
+//     "PRUN" u16 version
+//     strings:   u32 count, then u16 byte length + UTF-8 each
+//     stars:     u32 count, then sys_id name nat_id type (u32 string),
+//                x y z (f64), u16 connection count + u32 star index each
+//     planets:   u32 count, then u32 star index, name nat_id (u32 string),
+//                u8 surface, gravity temp pressure fertility (f64),
+//                u8 1 + u32 orbit index or u8 0 when none,
+//                u8 infrastructure count + u32 string each
+//     resources: u32 count, then u32 planet index, ticker type (u32 string),
+//                factor (f64)
+//
+// Repeated text (ids, types, tickers) is stored once in the string table,
+// and references between records are indices rather than ids. Version 1
+// files, without the orbit index and infrastructure, still unpack.
+//
+// Only the records are packed, not what Universe::build works out from
+// them (the filter index, resource stats and CX distances). The filter
+// worker builds those off the page's thread in a fraction of the time the
+// fetch takes, and the records can't be trusted without check_data anyway,
+// which can drop some and so change everything built from them. Packing
+// them too would mean a new version here whenever any of them changed.
+use anyhow::{anyhow, bail};
+use std::collections::HashMap;
+use std::convert::{TryFrom, TryInto};
+
+use crate::dataset::Dataset;
+use crate::models::{Connection, Planet, Resource, Star};
+
+pub const PACK_FILE: &str = "universe.bin";
+pub const PACK_VERSION: u16 = 2;
+const MAGIC: &[u8; 4] = b"PRUN";
+
+#[derive(Default)]
+struct Writer {
+    bytes: Vec<u8>,
+    strings: Vec<String>,
+    string_ids: HashMap<String, u32>,
+}
+
+impl Writer {
+    fn u8(&mut self, v: u8) {
+        self.bytes.push(v);
+    }
+    fn u16(&mut self, v: u16) {
+        self.bytes.extend_from_slice(&v.to_le_bytes());
+    }
+    fn u32(&mut self, v: u32) {
+        self.bytes.extend_from_slice(&v.to_le_bytes());
+    }
+    fn f64(&mut self, v: f64) {
+        self.bytes.extend_from_slice(&v.to_le_bytes());
+    }
+    fn len(&mut self, n: usize) -> anyhow::Result<()> {
+        self.u32(u32::try_from(n)?);
+        Ok(())
+    }
+    fn string(&mut self, s: &str) {
+        let id = match self.string_ids.get(s) {
+            Some(&id) => id,
+            None => {
+                let id = self.strings.len() as u32;
+                self.strings.push(s.to_string());
+                self.string_ids.insert(s.to_string(), id);
+                id
+            }
+        };
+        self.u32(id);
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    strings: Vec<String>,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> anyhow::Result<&'a [u8]> {
+        let end = self.pos.checked_add(n).filter(|&end| end <= self.bytes.len());
+        let end = end.ok_or_else(|| anyhow!("truncated at byte {}", self.pos))?;
+        let taken = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(taken)
+    }
+    fn u8(&mut self) -> anyhow::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+    fn u16(&mut self) -> anyhow::Result<u16> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into()?))
+    }
+    fn u32(&mut self) -> anyhow::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into()?))
+    }
+    fn f64(&mut self) -> anyhow::Result<f64> {
+        Ok(f64::from_le_bytes(self.take(8)?.try_into()?))
+    }
+    fn string(&mut self) -> anyhow::Result<String> {
+        let id = self.u32()? as usize;
+        self.strings.get(id).cloned().ok_or_else(|| anyhow!("bad string index {}", id))
+    }
+    fn index(&mut self, len: usize, what: &str) -> anyhow::Result<usize> {
+        let i = self.u32()? as usize;
+        if i >= len {
+            bail!("bad {} index {}", what, i);
+        }
+        Ok(i)
+    }
+}
+
+pub fn pack(data: &Dataset) -> anyhow::Result<Vec<u8>> {
+    let star_index: HashMap<&str, u32> = data.stars
+        .iter()
+        .enumerate()
+        .map(|(i, s)| (s.sys_id.as_str(), i as u32))
+        .collect();
+    let planet_index: HashMap<&str, u32> = data.planets
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (p.nat_id.as_str(), i as u32))
+        .collect();
+
+    // Records first, so the string table is complete before it's written
+    let mut w = Writer::default();
+    w.len(data.stars.len())?;
+    for s in &data.stars {
+        w.string(&s.sys_id);
+        w.string(&s.name);
+        w.string(&s.nat_id);
+        w.string(&s.typ);
+        w.f64(s.x);
+        w.f64(s.y);
+        w.f64(s.z);
+        w.u16(u16::try_from(s.connections.len())?);
+        for c in &s.connections {
+            let i = star_index.get(c.connection.as_str());
+            w.u32(*i.ok_or_else(|| anyhow!("{}: unknown connection {}", s.nat_id, c.connection))?);
+        }
+    }
+    w.len(data.planets.len())?;
+    for p in &data.planets {
+        let i = star_index.get(p.sys_id.as_str());
+        w.u32(*i.ok_or_else(|| anyhow!("{}: unknown system {}", p.nat_id, p.sys_id))?);
+        w.string(&p.name);
+        w.string(&p.nat_id);
+        w.u8(p.surface as u8);
+        w.f64(p.gravity);
+        w.f64(p.temp);
+        w.f64(p.pressure);
+        w.f64(p.fertility);
+        match p.orbit_index {
+            Some(orbit) => {
+                w.u8(1);
+                w.u32(orbit);
+            }
+            None => w.u8(0),
+        }
+        w.u8(u8::try_from(p.infrastructure.len())?);
+        for i in &p.infrastructure {
+            w.string(i);
+        }
+    }
+    w.len(data.resources.len())?;
+    for r in &data.resources {
+        let i = planet_index.get(r.planet.as_str());
+        w.u32(*i.ok_or_else(|| anyhow!("{} {}: unknown planet", r.planet, r.ticker))?);
+        w.string(&r.ticker);
+        w.string(&r.typ);
+        w.f64(r.factor);
+    }
+
+    let mut header = Writer::default();
+    header.bytes.extend_from_slice(MAGIC);
+    header.u16(PACK_VERSION);
+    header.len(w.strings.len())?;
+    for s in &w.strings {
+        header.u16(u16::try_from(s.len())?);
+        header.bytes.extend_from_slice(s.as_bytes());
+    }
+    header.bytes.extend_from_slice(&w.bytes);
+    Ok(header.bytes)
+}
+
+pub fn unpack(bytes: &[u8]) -> anyhow::Result<Dataset> {
+    let mut r = Reader { bytes, pos: 0, strings: Vec::new() };
+    if r.take(4)? != MAGIC {
+        bail!("not a packed dataset");
+    }
+    let version = r.u16()?;
+    if version == 0 || version > PACK_VERSION {
+        bail!("packed dataset version {}, expected up to {}", version, PACK_VERSION);
+    }
+    let count = r.u32()? as usize;
+    for _ in 0..count {
+        let len = r.u16()? as usize;
+        let s = std::str::from_utf8(r.take(len)?)?;
+        r.strings.push(s.to_string());
+    }
+
+    // The counts come from the file, so the vectors grow as records are
+    // actually read rather than being sized up front
+    let count = r.u32()? as usize;
+    let mut stars = Vec::new();
+    let mut connections = Vec::new();
+    for _ in 0..count {
+        stars.push(Star {
+            sys_id: r.string()?,
+            name: r.string()?,
+            nat_id: r.string()?,
+            typ: r.string()?,
+            x: r.f64()?,
+            y: r.f64()?,
+            z: r.f64()?,
+            ..Star::new()
+        });
+        let n = r.u16()?;
+        let star_connections: anyhow::Result<Vec<usize>> = (0..n).map(|_| r.index(count, "star")).collect();
+        connections.push(star_connections?);
+    }
+    for (i, star_connections) in connections.into_iter().enumerate() {
+        stars[i].connections = star_connections
+            .into_iter()
+            .map(|c| Connection { connection: stars[c].sys_id.clone() })
+            .collect();
+    }
+
+    let count = r.u32()? as usize;
+    let mut planets = Vec::new();
+    for _ in 0..count {
+        let star = r.index(stars.len(), "star")?;
+        let mut planet = Planet {
+            sys_id: stars[star].sys_id.clone(),
+            name: r.string()?,
+            nat_id: r.string()?,
+            surface: r.u8()? != 0,
+            gravity: r.f64()?,
+            temp: r.f64()?,
+            pressure: r.f64()?,
+            fertility: r.f64()?,
+            orbit_index: None,
+            infrastructure: Vec::new(),
+            unknown: Default::default(),
+        };
+        if version >= 2 {
+            if r.u8()? != 0 {
+                planet.orbit_index = Some(r.u32()?);
+            }
+            let n = r.u8()?;
+            for _ in 0..n {
+                planet.infrastructure.push(r.string()?);
+            }
+        }
+        planets.push(planet);
+    }
+
+    let count = r.u32()? as usize;
+    let mut resources = Vec::new();
+    for _ in 0..count {
+        let planet = r.index(planets.len(), "planet")?;
+        resources.push(Resource {
+            planet: planets[planet].nat_id.clone(),
+            ticker: r.string()?,
+            typ: r.string()?,
+            factor: r.f64()?,
+            unknown: Default::default(),
+        });
+    }
+    if r.pos != bytes.len() {
+        bail!("{} bytes left over", bytes.len() - r.pos);
+    }
+    Ok(Dataset { stars, planets, resources })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dataset() -> Dataset {
+        let stars = vec![
+            Star {
+                sys_id: "s1".to_string(),
+                name: "Antares".to_string(),
+                nat_id: "AN-001".to_string(),
+                typ: "M".to_string(),
+                x: 1.5,
+                y: -2.0,
+                z: 0.25,
+                connections: vec![Connection { connection: "s2".to_string() }],
+                ..Star::new()
+            },
+            Star {
+                sys_id: "s2".to_string(),
+                name: "Hortus".to_string(),
+                nat_id: "UV-351".to_string(),
+                typ: "G".to_string(),
+                connections: vec![Connection { connection: "s1".to_string() }],
+                ..Star::new()
+            },
+        ];
+        let planets = vec![Planet {
+            sys_id: "s2".to_string(),
+            name: "Montem".to_string(),
+            nat_id: "UV-351a".to_string(),
+            surface: true,
+            gravity: 0.98,
+            temp: 22.4,
+            pressure: 1.01,
+            fertility: 0.12,
+            orbit_index: Some(1),
+            infrastructure: vec!["localMarket".to_string()],
+            unknown: Default::default(),
+        }];
+        let resources = vec![Resource {
+            planet: "UV-351a".to_string(),
+            ticker: "FEO".to_string(),
+            typ: "MINERAL".to_string(),
+            factor: 0.31,
+            unknown: Default::default(),
+        }];
+        Dataset { stars, planets, resources }
+    }
+
+    #[test]
+    fn unpacks_what_was_packed() {
+        let data = dataset();
+        assert_eq!(unpack(&pack(&data).unwrap()).unwrap(), data);
+    }
+
+    #[test]
+    fn every_truncation_is_an_error() {
+        let bytes = pack(&dataset()).unwrap();
+        for len in 0..bytes.len() {
+            assert!(unpack(&bytes[..len]).is_err(), "{} bytes", len);
+        }
+    }
+
+    #[test]
+    fn huge_counts_fail_without_allocating() {
+        // No strings, then four billion stars
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&PACK_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+        bytes.extend_from_slice(&u32::MAX.to_le_bytes());
+        let e = unpack(&bytes).unwrap_err();
+        assert_eq!(e.to_string(), format!("truncated at byte {}", bytes.len()));
+    }
+}
@@ -1,4 +1,8 @@
-use crate::models::{Planet, Resource, Universe};
+use crate::extraction::{daily_output, ExtractionSettings};
+use crate::favourites::Favourite;
+use crate::system::favourite_class;
+use crate::models::{Normalisation, Planet, Resource, Universe, View};
+use crate::shared::Shared;
 use yew::prelude::*;
 
 #[derive(Clone, PartialEq, Properties)]
@@ -7,8 +11,14 @@ pub struct Props {
     pub resources: Vec<Resource>,
     pub highlight_env: bool,
     pub env_filter: bool,
-    pub universe: Universe,
+    pub universe: Shared<Universe>,
+    pub view: View,
+    pub extraction: ExtractionSettings,
+    pub pinned: bool,
+    pub favourite: Option<Favourite>,
     pub heading_click: Callback<String>,
+    pub pin_click: Callback<String>,
+    pub favourite_click: Callback<String>,
 }
 
 #[allow(dead_code)]
@@ -19,6 +29,8 @@ pub struct PlanetDetails {
 
 pub enum Msg {
     OnHeadingClick,
+    Pin,
+    Favourite,
 }
 
 impl Component for PlanetDetails {
@@ -45,13 +57,23 @@ impl Component for PlanetDetails {
                     .heading_click
                     .emit(self.props.planet.nat_id.to_owned());
             }
+            Msg::Favourite => {
+                self.props
+                    .favourite_click
+                    .emit(self.props.planet.nat_id.to_owned());
+            }
+            Msg::Pin => {
+                self.props
+                    .pin_click
+                    .emit(self.props.planet.nat_id.to_owned());
+            }
         }
         false
     }
 
     fn view(&self) -> Html {
         let p = &self.props.planet;
-        let p_filtered = self.props.env_filter && p.filtered;
+        let p_filtered = self.props.env_filter && self.props.view.is_filtered(p);
         let css_surface = if p.surface { "fa-mountain" } else { "fa-wind" };
         let css_filtered = if self.props.highlight_env && p_filtered {
             "filtered"
@@ -59,9 +81,11 @@ impl Component for PlanetDetails {
             ""
         };
         let class = format!("fas {} {}", css_surface, css_filtered);
-        let res_max_factor = &self.props.universe.res_max_factor;
-        let selected_res = &self.props.universe.selected_res;
+        let universe = &self.props.universe;
+        let selected_res = &self.props.view.selected_res;
+        let normalisation = self.props.view.normalisation;
         let highlight_env = self.props.highlight_env;
+        let extraction = &self.props.extraction;
 
         html! {
             <div class="icon-heading">
@@ -72,12 +96,28 @@ impl Component for PlanetDetails {
                 >
                     {&self.props.planet.name}
                 </h4>
+                <i
+                    class=classes!("fas", "fa-thumbtack", "pin", self.props.pinned.then_some("pinned"))
+                    title="Pin to compare"
+                    onclick=self.link.callback(|_e| Msg::Pin)
+                />
+                <i
+                    class=classes!("fa-star", "favourite", favourite_class(self.props.favourite.is_some()))
+                    title="Favourite planet"
+                    onclick=self.link.callback(|_e| Msg::Favourite)
+                />
+                { match &self.props.favourite {
+                    Some(f) if !f.note.is_empty() => html! { <div class="note">{&f.note}</div> },
+                    _ => html! {},
+                }}
                 <ul class="base-build" hidden=true></ul>
                 <ul>{for self.props.resources
                         .iter()
                         .map(|r| get_res_li(
                             r,
-                            res_max_factor.get(&r.ticker).unwrap(),
+                            universe,
+                            normalisation,
+                            daily_output(r, extraction),
                             highlight_env && match selected_res {
                                 Some(res) => r.ticker.eq(res),
                                 None => false,
@@ -94,9 +134,15 @@ impl Component for PlanetDetails {
     fn destroy(&mut self) {}
 }
 
-fn get_res_li(res: &Resource, max_factor: &f64, filtered: bool) -> Html {
+fn get_res_li(
+    res: &Resource,
+    universe: &Universe,
+    normalisation: Normalisation,
+    per_day: f64,
+    filtered: bool,
+) -> Html {
     let typ = format!("{}{}", res.typ[..1].to_uppercase(), &res.typ[1..]);
-    let conc = res.factor / max_factor;
+    let conc = universe.concentration(&res.ticker, res.factor, normalisation);
     let colour = match conc {
         c if c >= 0.66 => "conc-high",
         c if c >= 0.33 => "conc-medium",
@@ -104,13 +150,21 @@ fn get_res_li(res: &Resource, max_factor: &f64, filtered: bool) -> Html {
     };
     let v1 = (conc * 100.0).round() as i32;
     let v2 = (res.factor * 100.0).round() as i32;
-    let v3 = (max_factor * 100.0).round() as i32;
 
     let f = if filtered { "filtered" } else { "" };
     let ticker = format!("{} ({})", res.ticker, typ);
-    let ratio = format!(" {}% ({}/{})", v1, v2, v3);
+    let ratio = match normalisation {
+        Normalisation::PercentOfMax => {
+            let max_factor = universe.res_max_factor.get(&res.ticker).copied().unwrap_or(0.0);
+            format!(" {}% ({}/{})", v1, v2, (max_factor * 100.0).round() as i32)
+        }
+        Normalisation::Percentile => format!(" pctl {} ({})", v1, v2),
+    };
+    let per_day = format!(" {:.1}/d", per_day);
 
     html! {
-        <li class={f}>{ticker}<span class={colour}>{ratio}</span></li>
+        <li class={f} title=universe.material_name(&res.ticker).to_string()>
+            {ticker}<span class={colour}>{ratio}</span>{per_day}
+        </li>
     }
 }
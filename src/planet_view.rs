@@ -0,0 +1,193 @@
+use yew::prelude::*;
+
+use crate::extraction::{daily_output, ExtractionSettings, Extractor};
+use crate::models::{Environment, EnvironmentOption, Planet, Universe};
+use crate::shared::Shared;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    pub universe: Shared<Universe>,
+    pub planet_id: String,
+    pub extraction: ExtractionSettings,
+    pub pinned: bool,
+    pub close_signal: Callback<()>,
+    pub pin_signal: Callback<String>,
+    pub search_star_signal: Callback<String>,
+}
+
+pub enum Msg {
+    Close,
+    Pin,
+    SelectSystem(String),
+}
+
+#[allow(dead_code)]
+pub struct PlanetView {
+    link: ComponentLink<Self>,
+    props: Props,
+}
+
+impl PlanetView {
+    fn view_environment(&self, p: &Planet) -> Html {
+        let envs = [
+            (Environment::Gravity, "Gravity", 2),
+            (Environment::Temp, "Temperature", 0),
+            (Environment::Pressure, "Pressure", 2),
+        ];
+        html! {
+            <table class="planet-env">
+                { for envs.iter().map(|(env, label, dp)| {
+                    let (min, max) = env.normal_range();
+                    let level = p.env_level(env);
+                    let class = match level {
+                        EnvironmentOption::Low => "low",
+                        EnvironmentOption::High => "high",
+                        _ => "normal",
+                    };
+                    html! {
+                        <tr>
+                            <td>{label}</td>
+                            <td class={class}>{format!("{:.*}", *dp, p.env_value(env))}</td>
+                            <td>{format!("{} ({}..{})", level, min, max)}</td>
+                        </tr>
+                    }
+                })}
+                <tr>
+                    <td>{"Fertility"}</td>
+                    <td>{p.fertility_text()}</td>
+                    <td></td>
+                </tr>
+            </table>
+        }
+    }
+
+    fn view_resources(&self, p: &Planet) -> Html {
+        let universe = &self.props.universe;
+        let resources = universe.resources_for_planet(p);
+        if resources.is_empty() {
+            return html! { <div>{"No resources"}</div> };
+        }
+        html! {
+            <table class="planet-res">
+                <tr>
+                    <th>{"Resource"}</th><th>{"Factor"}</th><th>{"% Max"}</th>
+                    <th>{"Pctl"}</th><th>{"Per day"}</th>
+                </tr>
+                { for resources.iter().map(|r| {
+                    let max_factor = universe.res_max_factor.get(&r.ticker).copied().unwrap_or(0.0);
+                    let percent = if max_factor > 0.0 { r.factor / max_factor * 100.0 } else { 0.0 };
+                    let percentile = universe.res_percentile(&r.ticker, r.factor);
+                    let building = Extractor::for_type(&r.typ).map_or("", |e| e.ticker());
+                    let per_day = daily_output(r, &self.props.extraction);
+                    html! {
+                        <tr>
+                            <td>{format!("{} ({})", r.ticker, r.typ.to_lowercase())}</td>
+                            <td>{format!("{:.2}", r.factor)}</td>
+                            <td>{format!("{:.0}%", percent)}</td>
+                            <td>{format!("{:.0}", percentile)}</td>
+                            <td>{format!("{:.1} {}", per_day, building)}</td>
+                        </tr>
+                    }
+                })}
+            </table>
+        }
+    }
+
+    fn view_base_bill(&self, p: &Planet) -> Html {
+        let universe = &self.props.universe;
+        let bill = universe.base_bill(p);
+        let load = match universe.bill_load(&bill) {
+            Some((weight, volume)) => format!("{:.1} t, {:.1} m³", weight, volume),
+            None => "Weight and volume unknown".to_string(),
+        };
+        html! {
+            <ul class="base-bill">
+                { for bill.iter().map(|m| html! {
+                    <li title=universe.material_name(&m.ticker).to_string()>
+                        {format!("{} x {}", m.amount, m.ticker)}
+                    </li>
+                })}
+                <li class="bill-load">{load}</li>
+            </ul>
+        }
+    }
+}
+
+impl Component for PlanetView {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Self { link, props }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> bool {
+        if self.props != props {
+            self.props = props;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Close => self.props.close_signal.emit(()),
+            Msg::Pin => self.props.pin_signal.emit(self.props.planet_id.clone()),
+            Msg::SelectSystem(nat_id) => self.props.search_star_signal.emit(nat_id),
+        }
+        false
+    }
+
+    fn view(&self) -> Html {
+        let universe = &self.props.universe;
+        let p = match universe.planet_from_id(&self.props.planet_id) {
+            Some(p) => p,
+            None => return html! {},
+        };
+        let star = universe.star_for_planet(p);
+        let system = star.map(|s| s.name.clone()).unwrap_or_default();
+        let neighbours = star.map(|s| universe.neighbours(s)).unwrap_or_default();
+        let surface = if p.surface { "Rocky" } else { "Gaseous" };
+        let heading = if p.name != p.nat_id {
+            format!("{} ({})", p.name, p.nat_id)
+        } else {
+            p.name.clone()
+        };
+
+        html! {
+            <div class="planet-view">
+                <div class="planet-view-heading">
+                    <h3>{heading}</h3>
+                    <div>
+                        <button onclick=self.link.callback(|_| Msg::Pin)>
+                            {if self.props.pinned { "Unpin" } else { "Pin" }}
+                        </button>
+                        <button onclick=self.link.callback(|_| Msg::Close)>{"Close"}</button>
+                    </div>
+                </div>
+                <div>{format!("{} planet in the {} system", surface, system)}</div>
+                <h4 class="section">{"Environment"}</h4>
+                {self.view_environment(p)}
+                <h4 class="section">{"Resources"}</h4>
+                {self.view_resources(p)}
+                <h4 class="section">{"Base materials"}</h4>
+                {self.view_base_bill(p)}
+                <h4 class="section">{"Neighbouring systems"}</h4>
+                <ul>
+                { for neighbours.iter().map(|s| {
+                    let nat_id = s.nat_id.clone();
+                    html! {
+                        <li
+                            class="link"
+                            onclick=self.link.callback(move |_| Msg::SelectSystem(nat_id.clone()))
+                        >
+                            {&s.name}
+                        </li>
+                    }
+                })}
+                </ul>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use yew::format::Json;
+use yew::services::storage::{Area, StorageService};
+
+use crate::models::{Filters, MapFeatures};
+
+const PRESETS_KEY: &str = "prun.presets";
+const LAST_USED_KEY: &str = "prun.last-used";
+const LAST_MAP_KEY: &str = "prun.last-map";
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct Preset {
+    pub filters: Filters,
+    pub selected_res: Option<String>,
+}
+
+// How the map was last shown, kept apart from the presets as it isn't
+// part of what a preset filters
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct MapSettings {
+    pub scale: f64,
+    pub show_cx: bool,
+    pub show_routes: bool,
+}
+
+impl MapSettings {
+    pub fn of(map_features: &MapFeatures) -> Self {
+        Self {
+            scale: map_features.selected_scale,
+            show_cx: map_features.show_cx,
+            show_routes: map_features.show_routes,
+        }
+    }
+    pub fn apply(&self, map_features: &mut MapFeatures) {
+        map_features.set_selected_scale(self.scale);
+        map_features.show_cx = self.show_cx;
+        map_features.show_routes = self.show_routes;
+    }
+}
+
+pub enum PresetAction {
+    Save(String),
+    Load(String),
+    Rename(String, String),
+    Delete(String),
+}
+
+// Presets and the last used filters and map settings live in localStorage so they survive
+// a page reload. Without storage (private browsing etc.) they are kept in
+// memory for the session only.
+pub struct PresetStore {
+    storage: Option<StorageService>,
+    presets: BTreeMap<String, Preset>,
+}
+
+impl PresetStore {
+    pub fn new() -> Self {
+        let storage = StorageService::new(Area::Local).ok();
+        let presets = match &storage {
+            Some(s) => {
+                let Json(presets) = s.restore(PRESETS_KEY);
+                presets.unwrap_or_default()
+            }
+            None => BTreeMap::new(),
+        };
+        Self { storage, presets }
+    }
+    pub fn names(&self) -> Vec<String> {
+        self.presets.keys().cloned().collect()
+    }
+    pub fn get(&self, name: &str) -> Option<Preset> {
+        self.presets.get(name).cloned()
+    }
+    pub fn save(&mut self, name: String, preset: Preset) {
+        self.presets.insert(name, preset);
+        self.store_presets();
+    }
+    pub fn rename(&mut self, from: &str, to: String) {
+        if let Some(preset) = self.presets.remove(from) {
+            self.presets.insert(to, preset);
+            self.store_presets();
+        }
+    }
+    pub fn delete(&mut self, name: &str) {
+        if self.presets.remove(name).is_some() {
+            self.store_presets();
+        }
+    }
+    pub fn last_used(&self) -> Option<Preset> {
+        let Json(preset) = self.storage.as_ref()?.restore(LAST_USED_KEY);
+        preset.ok()
+    }
+    pub fn store_last_used(&mut self, preset: &Preset) {
+        if let Some(storage) = &mut self.storage {
+            storage.store(LAST_USED_KEY, Json(preset));
+        }
+    }
+    pub fn last_map(&self) -> Option<MapSettings> {
+        let Json(settings) = self.storage.as_ref()?.restore(LAST_MAP_KEY);
+        settings.ok()
+    }
+    pub fn store_last_map(&mut self, settings: &MapSettings) {
+        if let Some(storage) = &mut self.storage {
+            storage.store(LAST_MAP_KEY, Json(settings));
+        }
+    }
+    fn store_presets(&mut self) {
+        if let Some(storage) = &mut self.storage {
+            storage.store(PRESETS_KEY, Json(&self.presets));
+        }
+    }
+}
+
+impl Default for PresetStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,525 @@
+// A compact text form of Filters for power users, eg.
+//
+//     surface:rocky grav:0.5..1.8 temp:normal FEO>0.3 LST>40% jumps(ANT)<=5
+//
+// Anything not mentioned in the query is not filtered on. A ticker on its
+// own (eg. `FEO`) just selects the resource to show, and `normal:yes` makes
+// low and high include the normal range as the checkbox does.
+// `jumps(CX)<=4` is within 4 jumps of whichever CX is nearest, and
+// `dist(AX-383)<=300` within 300 map units of a system in a straight line.
+use std::fmt;
+
+use crate::models::*;
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct ParseError {
+    // In bytes, for slicing the input
+    pub pos: usize,
+    // In characters, for showing where
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.column + 1)
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct Query {
+    pub filters: Filters,
+    pub selected_res: Option<String>,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Number(f64),
+    Cmp(Comparison),
+    Colon,
+    DotDot,
+    Percent,
+    LParen,
+    RParen,
+    Space,
+}
+
+struct Lexer<'a> {
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+    input: &'a str,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(input: &'a str) -> Self {
+        Self { chars: input.char_indices().peekable(), input }
+    }
+    fn tokens(mut self) -> Result<Vec<(usize, Token)>, ParseError> {
+        let mut tokens = Vec::new();
+        while let Some(&(pos, c)) = self.chars.peek() {
+            let token = match c {
+                c if c.is_whitespace() => {
+                    while self.chars.peek().is_some_and(|(_, c)| c.is_whitespace()) {
+                        self.chars.next();
+                    }
+                    Token::Space
+                }
+                ':' => self.single(Token::Colon),
+                '%' => self.single(Token::Percent),
+                '(' => self.single(Token::LParen),
+                ')' => self.single(Token::RParen),
+                '<' | '>' | '=' => self.comparison(),
+                '.' => {
+                    self.chars.next();
+                    match self.chars.next() {
+                        Some((_, '.')) => Token::DotDot,
+                        _ => return Err(error(pos, "expected '..'")),
+                    }
+                }
+                '"' => self.string(pos)?,
+                c if c.is_ascii_digit() || c == '-' => self.number(pos)?,
+                c if c.is_alphabetic() => self.ident(),
+                c => return Err(error(pos, &format!("unexpected '{}'", c))),
+            };
+            tokens.push((pos, token));
+        }
+        Ok(tokens)
+    }
+    fn single(&mut self, token: Token) -> Token {
+        self.chars.next();
+        token
+    }
+    fn comparison(&mut self) -> Token {
+        let (_, c) = self.chars.next().unwrap();
+        let eq = self.chars.peek().is_some_and(|&(_, c)| c == '=');
+        if eq && c != '=' {
+            self.chars.next();
+        }
+        Token::Cmp(match (c, eq) {
+            ('<', true) => Comparison::Le,
+            ('<', false) => Comparison::Lt,
+            ('>', true) => Comparison::Ge,
+            ('>', false) => Comparison::Gt,
+            _ => Comparison::Eq,
+        })
+    }
+    fn string(&mut self, start: usize) -> Result<Token, ParseError> {
+        self.chars.next();
+        let mut s = String::new();
+        for (_, c) in self.chars.by_ref() {
+            if c == '"' {
+                return Ok(Token::Str(s));
+            }
+            s.push(c);
+        }
+        Err(error(start, "unterminated string"))
+    }
+    // Stops short of `..` so that ranges like 0.5..1.8 lex as three tokens
+    fn number(&mut self, start: usize) -> Result<Token, ParseError> {
+        let mut end = start;
+        if let Some(&(i, '-')) = self.chars.peek() {
+            self.chars.next();
+            end = i + 1;
+        }
+        while let Some(&(i, c)) = self.chars.peek() {
+            let is_fraction = c == '.'
+                && self.input[i + 1..].starts_with(|c: char| c.is_ascii_digit());
+            if c.is_ascii_digit() || is_fraction {
+                self.chars.next();
+                end = i + 1;
+            } else {
+                break;
+            }
+        }
+        self.input[start..end]
+            .parse()
+            .map(Token::Number)
+            .map_err(|_| error(start, "expected a number"))
+    }
+    // Natural ids like AX-383 are a single identifier
+    fn ident(&mut self) -> Token {
+        let mut s = String::new();
+        while let Some(&(_, c)) = self.chars.peek() {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                s.push(c);
+                self.chars.next();
+            } else {
+                break;
+            }
+        }
+        Token::Ident(s)
+    }
+}
+
+struct Parser<'a> {
+    tokens: Vec<(usize, Token)>,
+    next: usize,
+    end: usize,
+    // The tickers a bare word may be, None to take any that look like one
+    tickers: Option<&'a [String]>,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next).map(|(_, t)| t)
+    }
+    fn pos(&self) -> usize {
+        self.tokens.get(self.next).map_or(self.end, |(p, _)| *p)
+    }
+    fn bump(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.next).map(|(_, t)| t.clone());
+        self.next += 1;
+        token
+    }
+    fn expect(&mut self, token: Token, what: &str) -> Result<(), ParseError> {
+        let pos = self.pos();
+        match self.bump() {
+            Some(t) if t == token => Ok(()),
+            _ => Err(error(pos, &format!("expected {}", what))),
+        }
+    }
+    fn number(&mut self) -> Result<f64, ParseError> {
+        let pos = self.pos();
+        match self.bump() {
+            Some(Token::Number(n)) => Ok(n),
+            _ => Err(error(pos, "expected a number")),
+        }
+    }
+    fn comparison(&mut self) -> Result<Comparison, ParseError> {
+        let pos = self.pos();
+        match self.bump() {
+            Some(Token::Cmp(c)) => Ok(c),
+            _ => Err(error(pos, "expected one of < <= = >= >")),
+        }
+    }
+    fn query(&mut self) -> Result<Query, ParseError> {
+        let mut query = Query {
+            filters: Filters {
+                env_filter: true,
+                inc_normal: false,
+                surface: SurfaceOption::Both,
+                gravity: EnvironmentOption::Ignore,
+                temp: EnvironmentOption::Ignore,
+                pressure: EnvironmentOption::Ignore,
+                ..Filters::new()
+            },
+            selected_res: None,
+        };
+        loop {
+            while let Some(Token::Space) = self.peek() {
+                self.bump();
+            }
+            if self.peek().is_none() {
+                break;
+            }
+            self.term(&mut query)?;
+            if !matches!(self.peek(), None | Some(Token::Space)) {
+                return Err(error(self.pos(), "expected a space between terms"));
+            }
+        }
+        Ok(query)
+    }
+    fn term(&mut self, query: &mut Query) -> Result<(), ParseError> {
+        let pos = self.pos();
+        let ident = match self.bump() {
+            Some(Token::Ident(i)) => i,
+            _ => return Err(error(pos, "expected a filter or a ticker")),
+        };
+        let filters = &mut query.filters;
+        match ident.to_ascii_lowercase().as_str() {
+            "surface" => {
+                self.expect(Token::Colon, "':'")?;
+                let pos = self.pos();
+                filters.surface = match self.bump() {
+                    Some(Token::Ident(v)) => match v.to_ascii_lowercase().as_str() {
+                        "rocky" => SurfaceOption::Rocky,
+                        "gaseous" | "gas" => SurfaceOption::Gaseous,
+                        "both" | "any" => SurfaceOption::Both,
+                        _ => return Err(error(pos, "expected rocky, gaseous or both")),
+                    },
+                    _ => return Err(error(pos, "expected rocky, gaseous or both")),
+                };
+            }
+            "normal" => {
+                self.expect(Token::Colon, "':'")?;
+                let pos = self.pos();
+                filters.inc_normal = match self.bump() {
+                    Some(Token::Ident(v)) => match v.to_ascii_lowercase().as_str() {
+                        "yes" => true,
+                        "no" => false,
+                        _ => return Err(error(pos, "expected yes or no")),
+                    },
+                    _ => return Err(error(pos, "expected yes or no")),
+                };
+            }
+            "grav" | "gravity" => filters.gravity = self.environment()?,
+            "temp" | "temperature" => filters.temp = self.environment()?,
+            "pres" | "pressure" => filters.pressure = self.environment()?,
+            "jumps" => {
+                let origin = self.origin()?;
+                let cmp = self.comparison()?;
+                let pos = self.pos();
+                let jumps = self.number()?;
+                if jumps < 0.0 || jumps.fract() != 0.0 {
+                    return Err(error(pos, "expected a whole number of jumps"));
+                }
+                filters.jump_filters.push(JumpFilter {
+                    origin,
+                    cmp,
+                    jumps: jumps as u32,
+                });
+            }
+            "dist" | "distance" => {
+                let origin = self.origin()?;
+                let cmp = self.comparison()?;
+                let pos = self.pos();
+                let radius = self.number()?;
+                if radius < 0.0 {
+                    return Err(error(pos, "expected a distance of 0 or more"));
+                }
+                filters.radius_filters.push(RadiusFilter { origin, cmp, radius });
+            }
+            _ if self.is_ticker(&ident) => {
+                let ticker = ident.to_ascii_uppercase();
+                if query.selected_res.is_none() {
+                    query.selected_res = Some(ticker.clone());
+                }
+                if let Some(Token::Cmp(_)) = self.peek() {
+                    let cmp = self.comparison()?;
+                    let value = self.number()?;
+                    let percent = self.peek() == Some(&Token::Percent);
+                    if percent {
+                        self.bump();
+                    }
+                    filters.res_filters.push(ResFilter { ticker, cmp, value, percent });
+                }
+            }
+            _ => return Err(error(pos, &format!("unknown filter or ticker '{}'", ident))),
+        }
+        Ok(())
+    }
+    fn is_ticker(&self, s: &str) -> bool {
+        let looks_like_one = (1..=3).contains(&s.len()) && s.chars().all(|c| c.is_ascii_alphanumeric());
+        looks_like_one && self.tickers.is_none_or(|tickers| tickers.iter().any(|t| t.eq_ignore_ascii_case(s)))
+    }
+    // (ORIGIN) or ("Quoted Name")
+    fn origin(&mut self) -> Result<String, ParseError> {
+        self.expect(Token::LParen, "'('")?;
+        let pos = self.pos();
+        let origin = match self.bump() {
+            Some(Token::Ident(o)) | Some(Token::Str(o)) => o,
+            _ => return Err(error(pos, "expected a CX or system")),
+        };
+        self.expect(Token::RParen, "')'")?;
+        Ok(origin)
+    }
+    // normal, low, high, any or a range like 0.5..1.8
+    fn environment(&mut self) -> Result<EnvironmentOption, ParseError> {
+        self.expect(Token::Colon, "':'")?;
+        let pos = self.pos();
+        let expected = "expected normal, low, high, any or a range";
+        match self.bump() {
+            Some(Token::Ident(v)) => match v.to_ascii_lowercase().as_str() {
+                "normal" => Ok(EnvironmentOption::Normal),
+                "low" => Ok(EnvironmentOption::Low),
+                "high" => Ok(EnvironmentOption::High),
+                "any" | "ignore" => Ok(EnvironmentOption::Ignore),
+                _ => Err(error(pos, expected)),
+            },
+            Some(Token::Number(lo)) => {
+                self.expect(Token::DotDot, "'..'")?;
+                let hi_pos = self.pos();
+                let hi = self.number()?;
+                if hi < lo {
+                    return Err(error(hi_pos, "range end is below its start"));
+                }
+                Ok(EnvironmentOption::Range(lo, hi))
+            }
+            _ => Err(error(pos, expected)),
+        }
+    }
+}
+
+fn error(pos: usize, message: &str) -> ParseError {
+    ParseError { pos, column: pos, message: message.to_string() }
+}
+
+// A query as typed, where a word only counts as a ticker if it's one of
+// the given, so a typo isn't taken for a resource
+pub fn parse(input: &str, tickers: &[String]) -> Result<Query, ParseError> {
+    let tokens = Lexer::new(input).tokens().map_err(|e| at_column(input, e))?;
+    Parser { tokens, next: 0, end: input.len(), tickers: Some(tickers) }
+        .query()
+        .map_err(|e| at_column(input, e))
+}
+
+// For text from to_query, eg. in a shared link, which may well be read
+// before the data with the tickers has loaded
+pub fn parse_unchecked(input: &str) -> Result<Query, ParseError> {
+    let tokens = Lexer::new(input).tokens().map_err(|e| at_column(input, e))?;
+    Parser { tokens, next: 0, end: input.len(), tickers: None }
+        .query()
+        .map_err(|e| at_column(input, e))
+}
+
+fn at_column(input: &str, e: ParseError) -> ParseError {
+    let column = input.get(..e.pos).map_or(0, |text| text.chars().count());
+    ParseError { column, ..e }
+}
+
+// The query text for a set of filters, the inverse of parse
+pub fn to_query(filters: &Filters, selected_res: &Option<String>) -> String {
+    let mut terms = Vec::new();
+    if filters.env_filter {
+        match filters.surface {
+            SurfaceOption::Both => (),
+            ref s => terms.push(format!("surface:{}", s.to_string().to_lowercase())),
+        }
+        let envs = [
+            ("grav", &filters.gravity),
+            ("temp", &filters.temp),
+            ("pres", &filters.pressure),
+        ];
+        for (key, option) in envs.iter() {
+            match option {
+                EnvironmentOption::Ignore => (),
+                o => terms.push(format!("{}:{}", key, o.to_string().to_lowercase())),
+            }
+        }
+        let low_or_high = envs.iter().any(|(_, o)| {
+            matches!(o, EnvironmentOption::Low | EnvironmentOption::High)
+        });
+        if filters.inc_normal && low_or_high {
+            terms.push("normal:yes".to_string());
+        }
+    }
+    if let Some(res) = selected_res {
+        if !filters.res_filters.iter().any(|f| &f.ticker == res) {
+            terms.push(res.clone());
+        }
+    }
+    for f in &filters.res_filters {
+        let percent = if f.percent { "%" } else { "" };
+        terms.push(format!("{}{}{}{}", f.ticker, f.cmp, f.value, percent));
+    }
+    for f in &filters.jump_filters {
+        terms.push(format!("jumps({}){}{}", quote_origin(&f.origin), f.cmp, f.jumps));
+    }
+    for f in &filters.radius_filters {
+        terms.push(format!("dist({}){}{}", quote_origin(&f.origin), f.cmp, f.radius));
+    }
+    terms.join(" ")
+}
+
+fn quote_origin(origin: &str) -> String {
+    if origin.contains(' ') {
+        format!("\"{}\"", origin)
+    } else {
+        origin.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tickers() -> Vec<String> {
+        ["FEO", "LST", "H2O", "O"].iter().map(|t| t.to_string()).collect()
+    }
+
+    fn query(input: &str) -> Query {
+        parse(input, &tickers()).unwrap_or_else(|e| panic!("{}: {}", input, e))
+    }
+
+    fn error_at(input: &str) -> (usize, String) {
+        let e = parse(input, &tickers()).expect_err(input);
+        (e.pos, e.message)
+    }
+
+    #[test]
+    fn empty_query_filters_nothing() {
+        let q = query("  ");
+        assert_eq!(q.filters.surface, SurfaceOption::Both);
+        assert_eq!(q.filters.gravity, EnvironmentOption::Ignore);
+        assert_eq!(q.filters.temp, EnvironmentOption::Ignore);
+        assert_eq!(q.filters.pressure, EnvironmentOption::Ignore);
+        assert!(q.filters.res_filters.is_empty());
+        assert_eq!(q.selected_res, None);
+    }
+
+    #[test]
+    fn environment_terms() {
+        let q = query("surface:Gas grav:low temp:0.5..40 pres:any normal:yes");
+        assert_eq!(q.filters.surface, SurfaceOption::Gaseous);
+        assert_eq!(q.filters.gravity, EnvironmentOption::Low);
+        assert_eq!(q.filters.temp, EnvironmentOption::Range(0.5, 40.0));
+        assert_eq!(q.filters.pressure, EnvironmentOption::Ignore);
+        assert!(q.filters.inc_normal);
+        assert_eq!(query("temp:-25..-5").filters.temp, EnvironmentOption::Range(-25.0, -5.0));
+    }
+
+    #[test]
+    fn ticker_terms() {
+        let q = query("feo LST>40% H2O<=0.25");
+        assert_eq!(q.selected_res, Some("FEO".to_string()));
+        assert_eq!(q.filters.res_filters, vec![
+            ResFilter { ticker: "LST".to_string(), cmp: Comparison::Gt, value: 40.0, percent: true },
+            ResFilter { ticker: "H2O".to_string(), cmp: Comparison::Le, value: 0.25, percent: false },
+        ]);
+    }
+
+    #[test]
+    fn reach_terms() {
+        let q = query("jumps(ANT)<=5 dist(\"Antares I\")<300 jumps(CX)=2");
+        assert_eq!(q.filters.jump_filters, vec![
+            JumpFilter { origin: "ANT".to_string(), cmp: Comparison::Le, jumps: 5 },
+            JumpFilter { origin: "CX".to_string(), cmp: Comparison::Eq, jumps: 2 },
+        ]);
+        assert_eq!(q.filters.radius_filters, vec![
+            RadiusFilter { origin: "Antares I".to_string(), cmp: Comparison::Lt, radius: 300.0 },
+        ]);
+    }
+
+    #[test]
+    fn to_query_round_trips() {
+        let inputs = [
+            "surface:rocky grav:normal temp:low normal:yes FEO",
+            "grav:0.5..1.8 pres:high O>=0.1",
+            "FEO LST>40% jumps(ANT)<=5 dist(\"Antares I\")<300",
+            "",
+        ];
+        for input in inputs.iter() {
+            let q = query(input);
+            let text = to_query(&q.filters, &q.selected_res);
+            assert_eq!(query(&text), q, "{} gave {}", input, text);
+        }
+    }
+
+    #[test]
+    fn error_positions() {
+        assert_eq!(error_at("grav:bogus").0, 5);
+        assert_eq!(error_at("surface:rocky temp"), (18, "expected ':'".to_string()));
+        assert_eq!(error_at("FEO>").0, 4);
+        assert_eq!(error_at("FEO>0.3x").0, 7);
+        assert_eq!(error_at("jumps(ANT)<=2.5").0, 12);
+        assert_eq!(error_at("temp:40..10").0, 9);
+        assert_eq!(error_at("dist(\"Antares I)<3").0, 5);
+        assert_eq!(error_at("FEO !").0, 4);
+    }
+
+    #[test]
+    fn error_columns_are_characters() {
+        let input = "dist(\"Ĳssel\")<3 grav:x";
+        let e = parse(input, &tickers()).expect_err(input);
+        assert_eq!(e.pos, 22);
+        assert_eq!(e.column, 21);
+        assert_eq!(e.to_string(), "expected normal, low, high, any or a range at position 22");
+    }
+
+    #[test]
+    fn unknown_ticker_is_an_error() {
+        assert_eq!(error_at("grav:low gra"), (9, "unknown filter or ticker 'gra'".to_string()));
+        assert!(parse_unchecked("gra").is_ok());
+    }
+}
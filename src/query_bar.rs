@@ -0,0 +1,94 @@
+use yew::prelude::*;
+
+use crate::query::{parse, ParseError, Query};
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    // The tickers in the data, see query::parse
+    pub tickers: Vec<String>,
+    pub query_signal: Callback<Query>,
+}
+
+pub enum Msg {
+    SubmitQuery(ChangeData),
+}
+
+#[allow(dead_code)]
+pub struct QueryBar {
+    link: ComponentLink<Self>,
+    props: Props,
+    query: String,
+    error: Option<ParseError>,
+}
+
+impl Component for QueryBar {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Self {
+            link,
+            props,
+            query: String::new(),
+            error: None,
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> bool {
+        if self.props != props {
+            self.props = props;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::SubmitQuery(e) => {
+                if let ChangeData::Value(query) = e {
+                    self.error = match parse(&query, &self.props.tickers) {
+                        Ok(q) => {
+                            self.props.query_signal.emit(q);
+                            None
+                        }
+                        Err(e) => Some(e),
+                    };
+                    self.query = query;
+                }
+            }
+        }
+        true
+    }
+
+    fn view(&self) -> Html {
+        // Point at the offending character under the query
+        let error = match &self.error {
+            Some(e) => {
+                let caret = format!("{}^", "\u{a0}".repeat(e.column));
+                html! {
+                    <div class="error">
+                        <pre class="query-caret">{&self.query}{"\n"}{caret}</pre>
+                        {e.to_string()}
+                    </div>
+                }
+            }
+            None => html! {},
+        };
+
+        html! {
+            <div class="query-bar">
+                <div class="search-input">
+                    <label for="query">{"Query"}</label>
+                    <input
+                        class="query"
+                        name="query"
+                        placeholder="surface:rocky grav:normal FEO>0.3 jumps(ANT)<=5"
+                        onchange=self.link.callback(Msg::SubmitQuery)
+                    />
+                </div>
+                {error}
+            </div>
+        }
+    }
+}
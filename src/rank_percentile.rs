@@ -0,0 +1,67 @@
+use yew::prelude::*;
+
+use crate::models::Toggle;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    pub percentile: bool,
+    pub toggle_signal: Callback<(Toggle, bool)>,
+}
+
+pub enum Msg {
+    OnChange,
+}
+
+#[allow(dead_code)]
+pub struct RankPercentile {
+    link: ComponentLink<Self>,
+    props: Props,
+}
+
+impl Component for RankPercentile {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Self {
+            link,
+            props,
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> bool {
+        if self.props != props {
+            self.props = props;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::OnChange => {
+                let percentile = !self.props.percentile;
+                self.props.toggle_signal.emit((Toggle::Percentile, percentile));
+            }
+        }
+        false
+    }
+
+    fn view(&self) -> Html {
+        html! {
+            <div>
+                <label for="rank-percentile">
+                    <input
+                        type="checkbox"
+                        name="rank-percentile"
+                        checked=self.props.percentile
+                        onchange=self.link.callback(|_| Msg::OnChange)
+                    />
+                {"Rank by percentile"}
+                </label>
+            </div>
+        }
+    }
+}
+
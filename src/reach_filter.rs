@@ -0,0 +1,143 @@
+use yew::prelude::*;
+
+use crate::models::{Filters, Star};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ReachMode {
+    Jumps,
+    Radius,
+}
+
+pub enum ReachAction {
+    // Origin system or planet and the jumps or map units from it
+    Set(String, ReachMode, f64),
+    Clear,
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    pub filters: Filters,
+    pub selected_star: Option<Star>,
+    pub reach_signal: Callback<ReachAction>,
+}
+
+pub enum Msg {
+    Origin(String),
+    UseSelected,
+    Mode(ChangeData),
+    Limit(String),
+    Apply,
+    Clear,
+}
+
+#[allow(dead_code)]
+pub struct ReachFilter {
+    link: ComponentLink<Self>,
+    props: Props,
+    origin: String,
+    mode: ReachMode,
+    limit: String,
+}
+
+impl Component for ReachFilter {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Self {
+            link,
+            props,
+            origin: String::new(),
+            mode: ReachMode::Jumps,
+            limit: "5".to_string(),
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> bool {
+        if self.props != props {
+            self.props = props;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Origin(origin) => self.origin = origin,
+            Msg::UseSelected => {
+                if let Some(star) = &self.props.selected_star {
+                    self.origin = star.nat_id.clone();
+                }
+            }
+            Msg::Mode(ChangeData::Select(select)) => {
+                self.mode = match select.value().as_str() {
+                    "radius" => ReachMode::Radius,
+                    _ => ReachMode::Jumps,
+                };
+            }
+            Msg::Mode(_) => (),
+            Msg::Limit(limit) => self.limit = limit,
+            Msg::Apply => {
+                let origin = self.origin.trim();
+                if let (false, Ok(limit)) = (origin.is_empty(), self.limit.trim().parse::<f64>()) {
+                    if limit >= 0.0 {
+                        let action = ReachAction::Set(origin.to_string(), self.mode, limit);
+                        self.props.reach_signal.emit(action);
+                    }
+                }
+            }
+            Msg::Clear => self.props.reach_signal.emit(ReachAction::Clear),
+        }
+        true
+    }
+
+    fn view(&self) -> Html {
+        let filters = &self.props.filters;
+        let active = !filters.jump_filters.is_empty() || !filters.radius_filters.is_empty();
+        let unit = match self.mode {
+            ReachMode::Jumps => "jumps",
+            ReachMode::Radius => "map units",
+        };
+        html! {
+            <div class="reach-filter">
+                <h4 class="section">{"Reach"}</h4>
+                <div>
+                    <input
+                        type="text"
+                        class="reach-origin"
+                        placeholder="Origin system or planet"
+                        value=self.origin.clone()
+                        oninput=self.link.callback(|e: InputData| Msg::Origin(e.value))
+                    />
+                    <button
+                        disabled=self.props.selected_star.is_none()
+                        title="Use the system selected on the map"
+                        onclick=self.link.callback(|_| Msg::UseSelected)
+                    >
+                        {"Selected"}
+                    </button>
+                </div>
+                <div>
+                    {"Within "}
+                    <input
+                        type="number"
+                        class="reach-limit"
+                        min="0"
+                        value=self.limit.clone()
+                        oninput=self.link.callback(|e: InputData| Msg::Limit(e.value))
+                    />
+                    <select onchange=self.link.callback(Msg::Mode)>
+                        <option value="jumps" selected=self.mode == ReachMode::Jumps>{"jumps"}</option>
+                        <option value="radius" selected=self.mode == ReachMode::Radius>{"radius"}</option>
+                    </select>
+                    {format!(" ({})", unit)}
+                </div>
+                <div>
+                    <button onclick=self.link.callback(|_| Msg::Apply)>{"Apply"}</button>
+                    <button disabled=!active onclick=self.link.callback(|_| Msg::Clear)>{"Clear"}</button>
+                </div>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use yew::prelude::*;
+
+use crate::models::{Universe, View};
+use crate::shared::Shared;
+use crate::stats::{ResourceStats, HISTOGRAM_BINS};
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    pub universe: Shared<Universe>,
+    pub view: View,
+    pub search_star_signal: Callback<String>,
+}
+
+pub enum Msg {
+    SelectPlanet(String),
+}
+
+#[allow(dead_code)]
+pub struct ResourceDashboard {
+    link: ComponentLink<Self>,
+    props: Props,
+}
+
+impl ResourceDashboard {
+    fn view_histogram(&self, stats: &ResourceStats) -> Html {
+        let tallest = stats.histogram.iter().copied().max().unwrap_or(0).max(1);
+        let bin_width = stats.max / HISTOGRAM_BINS as f64;
+        html! {
+            <div class="histogram">
+                { for stats.histogram.iter().enumerate().map(|(i, &count)| {
+                    let width = format!("width: {}%;", count * 100 / tallest);
+                    let label = format!("{:.2}-{:.2}", i as f64 * bin_width, (i + 1) as f64 * bin_width);
+                    html! {
+                        <div class="histogram-row">
+                            <span class="histogram-label">{label}</span>
+                            <span class="histogram-bar" style=width></span>
+                            <span>{count}</span>
+                        </div>
+                    }
+                })}
+            </div>
+        }
+    }
+}
+
+impl Component for ResourceDashboard {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Self { link, props }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> bool {
+        if self.props != props {
+            self.props = props;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::SelectPlanet(nat_id) => self.props.search_star_signal.emit(nat_id),
+        }
+        false
+    }
+
+    fn view(&self) -> Html {
+        let universe = &self.props.universe;
+        let stats = match self.props.view.selected_res.as_ref().and_then(|r| universe.res_stats.get(r)) {
+            Some(stats) => stats,
+            None => return html! {},
+        };
+        let figures = [
+            ("Planets", stats.planet_count.to_string()),
+            ("Rocky / Gaseous", format!("{} / {}", stats.rocky, stats.gaseous)),
+            ("Mean", format!("{:.3}", stats.mean)),
+            ("Median", format!("{:.3}", stats.median)),
+            ("25th / 75th pctl", format!("{:.3} / {:.3}", stats.p25, stats.p75)),
+            ("90th pctl", format!("{:.3}", stats.p90)),
+            ("Best", format!("{:.3}", stats.max)),
+        ];
+
+        html! {
+            <div class="res-dashboard">
+                <h3>{format!("{} across the universe", stats.ticker)}</h3>
+                <table>
+                    { for figures.iter().map(|(label, value)| html! {
+                        <tr><td>{label}</td><td>{value}</td></tr>
+                    })}
+                </table>
+                <h4 class="section">{"Factor distribution"}</h4>
+                {self.view_histogram(stats)}
+                <h4 class="section">{format!("Top {} planets", stats.top.len())}</h4>
+                <ol>
+                { for stats.top.iter().map(|(nat_id, factor)| {
+                    let name = universe.planet_from_id(nat_id).map_or(nat_id.as_str(), |p| p.name.as_str());
+                    let id = nat_id.clone();
+                    html! {
+                        <li class="link" onclick=self.link.callback(move |_| Msg::SelectPlanet(id.clone()))>
+                            {format!("{} {:.3}", name, factor)}
+                        </li>
+                    }
+                })}
+                </ol>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use yew::prelude::*;
+
+use crate::extraction::{daily_output, ExtractionSettings};
+use crate::models::{Normalisation, Planet, Resource, Universe, View};
+use crate::shared::Shared;
+
+const PAGE_SIZE: usize = 15;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SortColumn {
+    Planet,
+    System,
+    Ticker,
+    Factor,
+    Concentration,
+    PerDay,
+    Gravity,
+    Temp,
+    Pressure,
+    Fertility,
+    Surface,
+    NearestCx,
+}
+
+const COLUMNS: [(SortColumn, &str); 12] = [
+    (SortColumn::Planet, "Planet"),
+    (SortColumn::System, "System"),
+    (SortColumn::Ticker, "Ticker"),
+    (SortColumn::Factor, "Factor"),
+    (SortColumn::Concentration, "% Max"),
+    (SortColumn::PerDay, "/Day"),
+    (SortColumn::Gravity, "Grav"),
+    (SortColumn::Temp, "Temp"),
+    (SortColumn::Pressure, "Pres"),
+    (SortColumn::Fertility, "Fert"),
+    (SortColumn::Surface, "Surface"),
+    (SortColumn::NearestCx, "CX"),
+];
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    pub universe: Shared<Universe>,
+    pub view: View,
+    pub extraction: ExtractionSettings,
+    pub pinned: Vec<String>,
+    pub search_star_signal: Callback<String>,
+    pub pin_signal: Callback<String>,
+}
+
+pub enum Msg {
+    Sort(SortColumn),
+    Page(usize),
+    RowClick(String),
+    Pin(String),
+}
+
+#[allow(dead_code)]
+pub struct ResultsTable {
+    link: ComponentLink<Self>,
+    props: Props,
+    sort: SortColumn,
+    ascending: bool,
+    page: usize,
+}
+
+struct Row<'a> {
+    planet: &'a Planet,
+    resource: &'a Resource,
+    system: &'a str,
+    conc: f64,
+    per_day: f64,
+    cx: Option<(&'static str, u32)>,
+}
+
+impl ResultsTable {
+    fn rows(&self) -> Vec<Row<'_>> {
+        let universe = &self.props.universe;
+        let systems: HashMap<&str, &str> = universe.stars
+            .iter()
+            .map(|s| (s.sys_id.as_str(), s.name.as_str()))
+            .collect();
+        let mut rows: Vec<Row> = universe.hits(&self.props.view.results)
+            .map(|(p, r)| {
+                Row {
+                    planet: p,
+                    resource: r,
+                    system: systems.get(p.sys_id.as_str()).copied().unwrap_or(""),
+                    conc: universe.concentration(&r.ticker, r.factor, self.props.view.normalisation) * 100.0,
+                    per_day: daily_output(r, &self.props.extraction),
+                    cx: universe.cx_distances.get(&p.sys_id).and_then(|d| d.jumps),
+                }
+            })
+            .collect();
+        let by_f64 = |a: f64, b: f64| a.partial_cmp(&b).unwrap_or(Ordering::Equal);
+        rows.sort_by(|a, b| {
+            let ord = match self.sort {
+                SortColumn::Planet => a.planet.name.cmp(&b.planet.name),
+                SortColumn::System => a.system.cmp(b.system),
+                SortColumn::Ticker => a.resource.ticker.cmp(&b.resource.ticker),
+                SortColumn::Factor => by_f64(a.resource.factor, b.resource.factor),
+                SortColumn::Concentration => by_f64(a.conc, b.conc),
+                SortColumn::PerDay => by_f64(a.per_day, b.per_day),
+                SortColumn::Gravity => by_f64(a.planet.gravity, b.planet.gravity),
+                SortColumn::Temp => by_f64(a.planet.temp, b.planet.temp),
+                SortColumn::Pressure => by_f64(a.planet.pressure, b.planet.pressure),
+                SortColumn::Fertility => by_f64(a.planet.fertility, b.planet.fertility),
+                SortColumn::Surface => a.planet.surface.cmp(&b.planet.surface),
+                // Unreachable sorts as furthest
+                SortColumn::NearestCx => {
+                    let jumps = |r: &Row| r.cx.map_or(u32::MAX, |(_, j)| j);
+                    jumps(a).cmp(&jumps(b))
+                }
+            };
+            if self.ascending { ord } else { ord.reverse() }
+        });
+        rows
+    }
+
+    fn view_row(&self, row: &Row) -> Html {
+        let p = row.planet;
+        let nat_id = p.nat_id.clone();
+        let pin_id = p.nat_id.clone();
+        let pinned = self.props.pinned.contains(&p.nat_id);
+        let surface = if p.surface { "Rocky" } else { "Gaseous" };
+        html! {
+            <tr onclick=self.link.callback(move |_| Msg::RowClick(nat_id.clone()))>
+                <td>
+                    {&p.name}
+                    <i
+                        class=classes!("fas", "fa-thumbtack", "pin", pinned.then_some("pinned"))
+                        title="Pin to compare"
+                        onclick=self.link.callback(move |e: MouseEvent| {
+                            // Not a click on the row as well
+                            e.stop_propagation();
+                            Msg::Pin(pin_id.clone())
+                        })
+                    />
+                </td>
+                <td>{row.system}</td>
+                <td>{&row.resource.ticker}</td>
+                <td>{format!("{:.2}", row.resource.factor)}</td>
+                <td>{match self.props.view.normalisation {
+                    Normalisation::PercentOfMax => format!("{:.0}%", row.conc),
+                    Normalisation::Percentile => format!("{:.0}", row.conc),
+                }}</td>
+                <td>{format!("{:.1}", row.per_day)}</td>
+                <td>{format!("{:.2}", p.gravity)}</td>
+                <td>{format!("{:.0}", p.temp)}</td>
+                <td>{format!("{:.2}", p.pressure)}</td>
+                <td>{p.fertility_text()}</td>
+                <td>{surface}</td>
+                <td>{row.cx.map_or("-".to_string(), |(code, j)| format!("{} {}", code, j))}</td>
+            </tr>
+        }
+    }
+}
+
+impl Component for ResultsTable {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Self {
+            link,
+            props,
+            sort: SortColumn::Factor,
+            ascending: false,
+            page: 0,
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> bool {
+        if self.props != props {
+            if self.props.view.results != props.view.results {
+                self.page = 0;
+            }
+            self.props = props;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Sort(column) => {
+                if self.sort == column {
+                    self.ascending = !self.ascending;
+                } else {
+                    self.sort = column;
+                    self.ascending = false;
+                }
+                self.page = 0;
+                true
+            }
+            Msg::Page(page) => {
+                self.page = page;
+                true
+            }
+            Msg::RowClick(nat_id) => {
+                self.props.search_star_signal.emit(nat_id);
+                false
+            }
+            Msg::Pin(nat_id) => {
+                self.props.pin_signal.emit(nat_id);
+                false
+            }
+        }
+    }
+
+    fn view(&self) -> Html {
+        let rows = self.rows();
+        if rows.is_empty() {
+            return html! {};
+        }
+        let pages = rows.len().div_ceil(PAGE_SIZE);
+        let page = self.page.min(pages - 1);
+        let title = format!("{} hits from the filter", rows.len());
+
+        html! {
+            <div class="results">
+                <h3>{title}</h3>
+                <table class="results-table">
+                    <thead>
+                        <tr>
+                        { for COLUMNS.iter().map(|&(column, label)| {
+                            let label = match (column, self.props.view.normalisation) {
+                                (SortColumn::Concentration, Normalisation::Percentile) => "Pctl",
+                                _ => label,
+                            };
+                            let arrow = match (self.sort == column, self.ascending) {
+                                (true, true) => " \u{25b2}",
+                                (true, false) => " \u{25bc}",
+                                _ => "",
+                            };
+                            html! {
+                                <th onclick=self.link.callback(move |_| Msg::Sort(column))>
+                                    {label}{arrow}
+                                </th>
+                            }
+                        })}
+                        </tr>
+                    </thead>
+                    <tbody>
+                    { for rows
+                        .iter()
+                        .skip(page * PAGE_SIZE)
+                        .take(PAGE_SIZE)
+                        .map(|row| self.view_row(row))
+                    }
+                    </tbody>
+                </table>
+                <div class="pager">
+                    <button
+                        disabled={page == 0}
+                        onclick=self.link.callback(move |_| Msg::Page(page.saturating_sub(1)))
+                    >
+                        {"Prev"}
+                    </button>
+                    {format!("Page {} of {}", page + 1, pages)}
+                    <button
+                        disabled={page + 1 >= pages}
+                        onclick=self.link.callback(move |_| Msg::Page(page + 1))
+                    >
+                        {"Next"}
+                    </button>
+                </div>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,200 @@
+// Versions of the data files' schema. A new version only ever adds
+// optional fields, so the app reads files of any earlier version. Fields
+// it doesn't know, eg. from a newer upstream, are skipped with a warning
+// rather than failing the load. Checking a file strictly against the
+// version it claims is left to prun-validate --strict.
+//
+//     1  the original fields
+//     2  planets gain orbitIndex and infrastructure
+use serde::de::IgnoredAny;
+use serde_derive::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::validate::{Issue, IssueKind, Severity};
+
+pub const SCHEMA_VERSION: u32 = 2;
+
+const STAR_FIELDS: [&str; 8] = ["sysId", "name", "natId", "type", "x", "y", "z", "connections"];
+const CONNECTION_FIELDS: [&str; 1] = ["connection"];
+const PLANET_FIELDS: [&str; 8] = ["sysId", "name", "natId", "surface", "gravity", "temp", "pressure", "fertility"];
+const PLANET_FIELDS_V2: [&str; 2] = ["orbitIndex", "infrastructure"];
+const RESOURCE_FIELDS: [&str; 4] = ["planet", "ticker", "type", "factor"];
+
+// Names of the fields a record had that the app doesn't know. Only there
+// to be reported: never written out, and ignored when comparing records.
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(transparent)]
+pub struct UnknownFields(BTreeMap<String, IgnoredAny>);
+
+impl UnknownFields {
+    pub fn names(&self) -> impl Iterator<Item = &String> {
+        self.0.keys()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl PartialEq for UnknownFields {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+// The fields a version allows in each file, None for an unknown version
+fn fields(version: u32, file: &str) -> Option<Vec<&'static str>> {
+    if version == 0 || version > SCHEMA_VERSION {
+        return None;
+    }
+    let mut fields = match file {
+        "stars" => STAR_FIELDS.to_vec(),
+        "planets" => PLANET_FIELDS.to_vec(),
+        "resources" => RESOURCE_FIELDS.to_vec(),
+        _ => return None,
+    };
+    if version >= 2 && file == "planets" {
+        fields.extend_from_slice(&PLANET_FIELDS_V2);
+    }
+    Some(fields)
+}
+
+// Every field in a file (stars, planets or resources) that the version
+// doesn't have, as errors
+pub fn strict_issues(version: u32, file: &'static str, records: &Value) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let allowed = match fields(version, file) {
+        Some(allowed) => allowed,
+        None => {
+            issues.push(Issue {
+                severity: Severity::Error,
+                kind: IssueKind::UnknownField,
+                file,
+                id: String::new(),
+                message: format!("schema version {} isn't known, this is version {}", version, SCHEMA_VERSION),
+            });
+            return issues;
+        }
+    };
+    let mut check = |record: &Value, allowed: &[&str], id: &str| {
+        if let Some(object) = record.as_object() {
+            for field in object.keys().filter(|k| !allowed.contains(&k.as_str())) {
+                issues.push(Issue {
+                    severity: Severity::Error,
+                    kind: IssueKind::UnknownField,
+                    file,
+                    id: id.to_string(),
+                    message: format!("field {} isn't in schema version {}", field, version),
+                });
+            }
+        }
+    };
+    for (i, record) in records.as_array().into_iter().flatten().enumerate() {
+        let id = match (record.get("natId"), record.get("planet"), record.get("ticker")) {
+            (Some(Value::String(nat_id)), _, _) => nat_id.clone(),
+            (_, Some(Value::String(planet)), Some(Value::String(ticker))) => format!("{} {}", planet, ticker),
+            _ => format!("record {}", i),
+        };
+        check(record, &allowed, &id);
+        if file == "stars" {
+            for connection in record.get("connections").and_then(Value::as_array).into_iter().flatten() {
+                check(connection, &CONNECTION_FIELDS, &id);
+            }
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    use crate::models::{Planet, Resource, Star};
+    use crate::validate::{has_errors, validate};
+
+    fn stars() -> Value {
+        json!([{
+            "sysId": "s1", "name": "Antares", "natId": "AN-001", "type": "M",
+            "x": 0.0, "y": 1.0, "z": 2.0, "connections": [],
+        }])
+    }
+
+    fn planets(version: u32) -> Value {
+        let mut planet = json!({
+            "sysId": "s1", "name": "Montem", "natId": "AN-001a", "surface": true,
+            "gravity": 0.98, "temp": 22.4, "pressure": 1.01, "fertility": 0.1,
+        });
+        if version >= 2 {
+            planet["orbitIndex"] = json!(1);
+            planet["infrastructure"] = json!(["localMarket"]);
+        }
+        json!([planet])
+    }
+
+    fn resources() -> Value {
+        json!([{"planet": "AN-001a", "ticker": "FEO", "type": "MINERAL", "factor": 0.3}])
+    }
+
+    #[test]
+    fn each_version_passes_its_own_check() {
+        for version in 1..=SCHEMA_VERSION {
+            assert!(strict_issues(version, "stars", &stars()).is_empty());
+            assert!(strict_issues(version, "planets", &planets(version)).is_empty(), "version {}", version);
+            assert!(strict_issues(version, "resources", &resources()).is_empty());
+        }
+    }
+
+    #[test]
+    fn later_fields_fail_an_earlier_version() {
+        let issues = strict_issues(1, "planets", &planets(2));
+        let fields: Vec<&str> = issues.iter().map(|i| i.message.split(' ').nth(1).unwrap()).collect();
+        assert_eq!(fields, ["infrastructure", "orbitIndex"]);
+        assert!(issues.iter().all(|i| i.severity == Severity::Error && i.id == "AN-001a"));
+    }
+
+    #[test]
+    fn unknown_versions_are_errors() {
+        for version in [0, SCHEMA_VERSION + 1] {
+            let issues = strict_issues(version, "stars", &stars());
+            assert_eq!(issues.len(), 1);
+            assert_eq!(issues[0].kind, IssueKind::UnknownField);
+            assert!(has_errors(&issues));
+        }
+    }
+
+    #[test]
+    fn connection_fields_are_checked() {
+        let mut stars = stars();
+        stars[0]["connections"] = json!([{"connection": "s2", "jumps": 1}]);
+        let issues = strict_issues(SCHEMA_VERSION, "stars", &stars);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].message, format!("field jumps isn't in schema version {}", SCHEMA_VERSION));
+    }
+
+    #[test]
+    fn unknown_fields_load_with_a_warning() {
+        let mut planets = planets(SCHEMA_VERSION);
+        planets[0]["moons"] = json!(3);
+        let stars: Vec<Star> = serde_json::from_value(stars()).unwrap();
+        let planets: Vec<Planet> = serde_json::from_value(planets).unwrap();
+        let resources: Vec<Resource> = serde_json::from_value(resources()).unwrap();
+        assert_eq!(planets[0].unknown.names().collect::<Vec<_>>(), ["moons"]);
+        assert!(stars[0].unknown.is_empty());
+
+        let issues = validate(&stars, &planets, &resources);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, Severity::Warning);
+        assert_eq!(issues[0].kind, IssueKind::UnknownField);
+        assert_eq!((issues[0].file, issues[0].id.as_str()), ("planets", "moons"));
+        assert!(!has_errors(&issues));
+    }
+
+    #[test]
+    fn unknown_fields_are_not_written() {
+        let mut planets = planets(SCHEMA_VERSION);
+        planets[0]["moons"] = json!(3);
+        let planets: Vec<Planet> = serde_json::from_value(planets).unwrap();
+        assert_eq!(serde_json::to_value(&planets).unwrap(), self::planets(SCHEMA_VERSION));
+    }
+}
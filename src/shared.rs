@@ -0,0 +1,44 @@
+// An Rc for props. Equal only when both point at the same value, so a
+// component's change() doesn't walk the whole of a big value to find it
+// hasn't changed. Anything that changes it has to make a new one, or go
+// through make_mut.
+use std::ops::Deref;
+use std::rc::Rc;
+use yew::html::ImplicitClone;
+
+#[derive(Debug, Default)]
+pub struct Shared<T>(Rc<T>);
+
+impl<T> Shared<T> {
+    pub fn new(value: T) -> Self {
+        Self(Rc::new(value))
+    }
+}
+
+impl<T: Clone> Shared<T> {
+    // Copies the value first if anything else still shares it
+    pub fn make_mut(&mut self) -> &mut T {
+        Rc::make_mut(&mut self.0)
+    }
+}
+
+impl<T> Clone for Shared<T> {
+    fn clone(&self) -> Self {
+        Self(Rc::clone(&self.0))
+    }
+}
+
+impl<T> PartialEq for Shared<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl<T> Deref for Shared<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> ImplicitClone for Shared<T> {}
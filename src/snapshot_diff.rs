@@ -0,0 +1,166 @@
+use yew::prelude::*;
+use yew::services::reader::{FileData, ReaderService, ReaderTask};
+
+use crate::diff::{Change, ChangeKind};
+use crate::models::Universe;
+use crate::shared::Shared;
+
+// Rows shown per kind of change, the rest are counted
+const MAX_ROWS: usize = 25;
+
+pub enum SnapshotAction {
+    // The file name and its contents, a packed dataset
+    Load(String, Vec<u8>),
+    Clear,
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    pub universe: Shared<Universe>,
+    // The loaded snapshot's name and its changes to the current data
+    pub diff: Option<(String, Vec<Change>)>,
+    // Why the last snapshot loaded couldn't be compared
+    pub error: Option<String>,
+    pub snapshot_signal: Callback<SnapshotAction>,
+    pub search_star_signal: Callback<String>,
+}
+
+pub enum Msg {
+    LoadFile(ChangeData),
+    Loaded(FileData),
+    Clear,
+    Select(String),
+}
+
+#[allow(dead_code)]
+pub struct SnapshotDiff {
+    link: ComponentLink<Self>,
+    props: Props,
+    reader_task: Option<ReaderTask>,
+}
+
+impl SnapshotDiff {
+    fn view_kind(&self, kind: ChangeKind, changes: &[&Change]) -> Html {
+        let universe = &self.props.universe;
+        html! {
+            <li>
+                <span class="diff-kind">{format!("{} ({})", kind.label(), changes.len())}</span>
+                <ul>
+                { for changes.iter().take(MAX_ROWS).map(|c| {
+                    // Removed systems aren't on the map to go to
+                    let star = universe.stars.iter().find(|s| s.sys_id == c.sys_id);
+                    let id = match star {
+                        Some(star) => {
+                            let nat_id = star.nat_id.clone();
+                            html! {
+                                <span class="link" onclick=self.link.callback(move |_| Msg::Select(nat_id.clone()))>
+                                    {&c.id}
+                                </span>
+                            }
+                        }
+                        None => html! { <span>{&c.id}</span> },
+                    };
+                    html! { <li>{id}{format!(" {}", c.message)}</li> }
+                })}
+                { if changes.len() > MAX_ROWS {
+                    html! { <li>{format!("and {} more", changes.len() - MAX_ROWS)}</li> }
+                } else {
+                    html! {}
+                }}
+                </ul>
+            </li>
+        }
+    }
+}
+
+impl Component for SnapshotDiff {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Self {
+            link,
+            props,
+            reader_task: None,
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> bool {
+        if self.props != props {
+            self.props = props;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::LoadFile(ChangeData::Files(files)) => {
+                if let Some(file) = files.get(0) {
+                    let callback = self.link.callback(Msg::Loaded);
+                    self.reader_task = ReaderService::read_file(file, callback).ok();
+                }
+                false
+            }
+            Msg::LoadFile(_) => false,
+            Msg::Loaded(data) => {
+                self.reader_task = None;
+                self.props.snapshot_signal.emit(SnapshotAction::Load(data.name, data.content));
+                false
+            }
+            Msg::Clear => {
+                self.props.snapshot_signal.emit(SnapshotAction::Clear);
+                false
+            }
+            Msg::Select(nat_id) => {
+                self.props.search_star_signal.emit(nat_id);
+                false
+            }
+        }
+    }
+
+    fn view(&self) -> Html {
+        let report = match &self.props.diff {
+            Some((name, changes)) => {
+                let mut kinds: Vec<(ChangeKind, Vec<&Change>)> = Vec::new();
+                for c in changes {
+                    match kinds.last_mut() {
+                        Some((kind, group)) if *kind == c.kind => group.push(c),
+                        _ => kinds.push((c.kind, vec![c])),
+                    }
+                }
+                html! {
+                    <>
+                        <div>
+                            {format!("{} changes since {}", changes.len(), name)}
+                            <button onclick=self.link.callback(|_| Msg::Clear)>{"Clear"}</button>
+                        </div>
+                        <ul class="diff-report">
+                            { for kinds.iter().map(|(kind, group)| self.view_kind(*kind, group)) }
+                        </ul>
+                    </>
+                }
+            }
+            None => html! { <div>{"Load an earlier universe.bin to see what has changed"}</div> },
+        };
+        html! {
+            <div class="snapshot-diff">
+                <h3>{"Data Changes"}</h3>
+                {report}
+                <label class="import">
+                    {"Compare with snapshot"}
+                    <input
+                        type="file"
+                        accept=".bin,application/octet-stream"
+                        onchange=self.link.callback(Msg::LoadFile)
+                    />
+                </label>
+                { match &self.props.error {
+                    Some(e) => html! { <div class="error">{e}</div> },
+                    None => html! {},
+                }}
+            </div>
+        }
+    }
+}
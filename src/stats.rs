@@ -0,0 +1,187 @@
+// How a resource is spread across the universe, to judge whether a planet's
+// factor is actually any good.
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::models::{Planet, Resource};
+
+pub const HISTOGRAM_BINS: usize = 10;
+pub const TOP_PLANETS: usize = 10;
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceStats {
+    pub ticker: String,
+    pub planet_count: usize,
+    pub mean: f64,
+    pub median: f64,
+    pub p25: f64,
+    pub p75: f64,
+    pub p90: f64,
+    pub max: f64,
+    // Planet counts in equal width bins from 0 to max
+    pub histogram: Vec<usize>,
+    pub rocky: usize,
+    pub gaseous: usize,
+    // Planet nat_ids and factors, best first
+    pub top: Vec<(String, f64)>,
+}
+
+// Linear interpolation between the closest ranks, p in 0-100
+pub fn percentile(sorted: &[f64], p: f64) -> f64 {
+    match sorted.len() {
+        0 => 0.0,
+        1 => sorted[0],
+        n => {
+            let rank = p.clamp(0.0, 100.0) / 100.0 * (n - 1) as f64;
+            let lo = rank.floor() as usize;
+            let hi = rank.ceil() as usize;
+            sorted[lo] + (sorted[hi] - sorted[lo]) * (rank - lo as f64)
+        }
+    }
+}
+
+pub fn resource_stats(
+    ticker: &str,
+    resources: &[Resource],
+    planets: &HashMap<&str, &Planet>,
+) -> ResourceStats {
+    // A factor that isn't a number says nothing about the spread
+    let mut found: Vec<&Resource> = resources
+        .iter()
+        .filter(|r| r.ticker == ticker && r.factor.is_finite())
+        .collect();
+    found.sort_by(|a, b| b.factor.total_cmp(&a.factor));
+    let mut factors: Vec<f64> = found.iter().map(|r| r.factor).collect();
+    factors.reverse();
+
+    let n = factors.len();
+    let max = factors.last().copied().unwrap_or(0.0);
+    let mut histogram = vec![0; HISTOGRAM_BINS];
+    if max > 0.0 {
+        for f in &factors {
+            let bin = ((f / max) * HISTOGRAM_BINS as f64) as usize;
+            histogram[bin.min(HISTOGRAM_BINS - 1)] += 1;
+        }
+    }
+    // Resources on a planet that can't be found count as neither
+    let surfaces: Vec<bool> = found
+        .iter()
+        .filter_map(|r| planets.get(r.planet.as_str()).map(|p| p.surface))
+        .collect();
+    let rocky = surfaces.iter().filter(|&&surface| surface).count();
+    let gaseous = surfaces.iter().filter(|&&surface| !surface).count();
+
+    ResourceStats {
+        ticker: ticker.to_string(),
+        planet_count: n,
+        mean: if n > 0 { factors.iter().sum::<f64>() / n as f64 } else { 0.0 },
+        median: percentile(&factors, 50.0),
+        p25: percentile(&factors, 25.0),
+        p75: percentile(&factors, 75.0),
+        p90: percentile(&factors, 90.0),
+        max,
+        histogram,
+        rocky,
+        gaseous,
+        top: found
+            .iter()
+            .take(TOP_PLANETS)
+            .map(|r| (r.planet.clone(), r.factor))
+            .collect(),
+    }
+}
+
+pub fn all_resource_stats(
+    tickers: &[String],
+    resources: &[Resource],
+    planets: &[Planet],
+) -> HashMap<String, ResourceStats> {
+    let planets: HashMap<&str, &Planet> = planets.iter().map(|p| (p.nat_id.as_str(), p)).collect();
+    tickers
+        .iter()
+        .map(|t| (t.clone(), resource_stats(t, resources, &planets)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn planet(nat_id: &str, surface: bool) -> Planet {
+        Planet {
+            sys_id: "s1".to_string(),
+            name: nat_id.to_string(),
+            nat_id: nat_id.to_string(),
+            surface,
+            gravity: 1.0,
+            temp: 20.0,
+            pressure: 1.0,
+            fertility: 0.0,
+            orbit_index: None,
+            infrastructure: Vec::new(),
+            unknown: Default::default(),
+        }
+    }
+
+    fn resource(planet: &str, ticker: &str, factor: f64) -> Resource {
+        Resource {
+            planet: planet.to_string(),
+            ticker: ticker.to_string(),
+            typ: "MINERAL".to_string(),
+            factor,
+            unknown: Default::default(),
+        }
+    }
+
+    #[test]
+    fn percentile_interpolates_between_ranks() {
+        let sorted = [0.1, 0.2, 0.4, 0.8];
+        assert_eq!(percentile(&sorted, 0.0), 0.1);
+        assert_eq!(percentile(&sorted, 100.0), 0.8);
+        assert!((percentile(&sorted, 50.0) - 0.3).abs() < 1e-12);
+        assert!((percentile(&sorted, 25.0) - 0.175).abs() < 1e-12);
+        assert!((percentile(&sorted, 90.0) - 0.68).abs() < 1e-12);
+        // Out of range p is clamped
+        assert_eq!(percentile(&sorted, -5.0), 0.1);
+        assert_eq!(percentile(&sorted, 120.0), 0.8);
+    }
+
+    #[test]
+    fn percentile_of_few_values() {
+        assert_eq!(percentile(&[], 50.0), 0.0);
+        assert_eq!(percentile(&[0.3], 90.0), 0.3);
+    }
+
+    #[test]
+    fn stats_of_a_resource() {
+        let planets = vec![planet("A", true), planet("B", false), planet("C", true)];
+        let resources = vec![
+            resource("A", "FEO", 0.2),
+            resource("B", "FEO", 0.4),
+            resource("C", "FEO", 0.1),
+            resource("D", "FEO", 0.3),
+            resource("A", "H2O", 0.9),
+            resource("C", "FEO", f64::NAN),
+        ];
+        let stats = &all_resource_stats(&["FEO".to_string()], &resources, &planets)["FEO"];
+        assert_eq!(stats.planet_count, 4);
+        assert_eq!(stats.max, 0.4);
+        assert!((stats.mean - 0.25).abs() < 1e-12);
+        assert!((stats.median - 0.25).abs() < 1e-12);
+        // D isn't a known planet, so is neither rocky nor gaseous
+        assert_eq!((stats.rocky, stats.gaseous), (2, 1));
+        assert_eq!(stats.histogram.iter().sum::<usize>(), 4);
+        assert_eq!(stats.histogram[HISTOGRAM_BINS - 1], 1);
+        let top: Vec<&str> = stats.top.iter().map(|(p, _)| p.as_str()).collect();
+        assert_eq!(top, ["B", "D", "A", "C"]);
+    }
+
+    #[test]
+    fn stats_of_a_missing_resource() {
+        let stats = resource_stats("LST", &[], &HashMap::new());
+        assert_eq!(stats.planet_count, 0);
+        assert_eq!((stats.mean, stats.max), (0.0, 0.0));
+        assert!(stats.histogram.iter().all(|&n| n == 0));
+    }
+}
@@ -1,13 +1,24 @@
 use yew::prelude::*;
 
-use crate::models::{Planet, Resource, Universe};
-use crate::planet_details::PlanetDetails;
+use crate::models::{Universe, View};
+use crate::shared::Shared;
 
 #[derive(Clone, PartialEq, Properties)]
 pub struct Props {
-    pub universe: Universe,
-    pub env_filter: bool,
-    pub search_star_signal: Callback<String>,
+    pub universe: Shared<Universe>,
+    pub view: View,
+    // Waiting on the filter worker
+    pub busy: bool,
+    pub query: String,
+    pub pinned: Vec<String>,
+    pub pin_signal: Callback<String>,
+}
+
+// Enough to choose a base site between
+const PIN_TOP: usize = 3;
+
+pub enum Msg {
+    PinTop,
 }
 
 #[allow(dead_code)]
@@ -16,10 +27,6 @@ pub struct Summary {
     props: Props,
 }
 
-pub enum Msg {
-    OnHeadingClick(String),
-}
-
 impl Component for Summary {
     type Message = Msg;
     type Properties = Props;
@@ -39,16 +46,29 @@ impl Component for Summary {
 
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match msg {
-            Msg::OnHeadingClick(name) => {
-                self.props.search_star_signal.emit(name);
+            // The best planets for the selected resource not already pinned
+            Msg::PinTop => {
+                let mut planets: Vec<&str> = Vec::new();
+                for (p, _) in self.props.universe.hits(&self.props.view.results) {
+                    if planets.len() == PIN_TOP {
+                        break;
+                    }
+                    if !planets.contains(&p.nat_id.as_str()) && !self.props.pinned.contains(&p.nat_id) {
+                        planets.push(&p.nat_id);
+                    }
+                }
+                for nat_id in planets {
+                    self.props.pin_signal.emit(nat_id.to_string());
+                }
             }
         }
         false
     }
 
     fn view(&self) -> Html {
-        let selected_res = &self.props.universe.selected_res;
-        let diagnostics = &self.props.universe.diagnostics;
+        let selected_res = &self.props.view.selected_res;
+        let diagnostics = &self.props.view.results.diagnostics;
+        let issues = &self.props.universe.data_issues;
         let summary = match selected_res {
             Some(res) => {
                 format!("Filter: {} Planets, {} Star Systems with {} resources",
@@ -62,37 +82,32 @@ impl Component for Summary {
                     diagnostics.stars_with_planets_with_env_res)
             }
         };
-        let top_hits: Vec::<(Planet, Resource)> = diagnostics.filter_hits
-            .iter()
-            .take(8)
-            .cloned()
-            .collect();
-        let title = format!("Top {} hits from the filter", top_hits.len());
 
         html! {
             <div class="diagnostics">
                 <div style="margin: '0.5rem';">
                     {summary}
+                    <button
+                        class="pin-top"
+                        title="Pin the best planets to compare"
+                        hidden={diagnostics.filter_hits.is_empty()}
+                        onclick=self.link.callback(|_| Msg::PinTop)
+                    >
+                        {format!("Pin top {}", PIN_TOP)}
+                    </button>
+                    <i
+                        class="fas fa-circle-notch fa-spin busy"
+                        title="Filtering"
+                        hidden={!self.props.busy}
+                    />
+                </div>
+                <div class="query-text" hidden={self.props.query.is_empty()}>
+                    {"Query: "}<code>{&self.props.query}</code>
+                </div>
+                <div class="data-issues" hidden={issues.is_empty()}>
+                    {format!("Data check: {} problems, see the console", issues.len())}
                 </div>
-                <h3>{if !top_hits.is_empty() {title} else {"".to_string()}}</h3>
-                { for top_hits
-                    .into_iter()
-                    .map(|(p, r)| {
-                        let resources = vec![r];
-                        html! {
-                            <PlanetDetails
-                                planet={p}
-                                resources={resources}
-                                highlight_env=false
-                                env_filter={self.props.env_filter}
-                                universe={&self.props.universe}
-                                heading_click=self.link.callback(Msg::OnHeadingClick)
-                            />
-                        }
-                    })
-                }
             </div>
         }
     }
 }
-
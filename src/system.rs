@@ -1,13 +1,22 @@
 use yew::prelude::*;
-use yew::services::ConsoleService;
 
-use crate::models::Universe;
+use crate::extraction::ExtractionSettings;
+use crate::favourites::{FavouriteAction, FavouriteKind, Favourites};
+use crate::models::{CxDistance, Star, Universe, View};
 use crate::planet_details::PlanetDetails;
+use crate::shared::Shared;
 
 #[derive(Clone, PartialEq, Properties)]
 pub struct Props {
-    pub universe: Universe,
+    pub universe: Shared<Universe>,
+    pub view: View,
     pub env_filter: bool,
+    pub extraction: ExtractionSettings,
+    pub pinned: Vec<String>,
+    pub favourites: Favourites,
+    pub planet_signal: Callback<String>,
+    pub pin_signal: Callback<String>,
+    pub favourite_signal: Callback<FavouriteAction>,
 }
 
 #[allow(dead_code)]
@@ -18,6 +27,26 @@ pub struct System {
 
 pub enum Msg {
     OnHeadingClick(String),
+    Pin(String),
+    Favourite(FavouriteKind, String),
+}
+
+impl System {
+    fn view_nearest_cx(&self, star: &Star) -> Html {
+        let text = match self.props.universe.cx_distances.get(&star.sys_id) {
+            Some(CxDistance { jumps, distance }) => {
+                let by_jumps = jumps.map_or("none reachable".to_string(), |(code, j)| {
+                    format!("{} in {} jumps", code, j)
+                });
+                let by_distance = distance.map_or(String::new(), |(code, d)| {
+                    format!(", closest {} at {:.0} map units", code, d)
+                });
+                format!("Nearest CX {}{}", by_jumps, by_distance)
+            }
+            None => return html! {},
+        };
+        html! { <div class="nearest-cx">{text}</div> }
+    }
 }
 
 impl Component for System {
@@ -39,23 +68,44 @@ impl Component for System {
 
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match msg {
-            Msg::OnHeadingClick(_s) => {
-                // TODO
-                ConsoleService::info("Feature not implimented yet");
+            Msg::OnHeadingClick(nat_id) => {
+                self.props.planet_signal.emit(nat_id);
+            }
+            Msg::Pin(nat_id) => self.props.pin_signal.emit(nat_id),
+            Msg::Favourite(kind, nat_id) => {
+                self.props.favourite_signal.emit(FavouriteAction::Toggle(kind, nat_id));
             }
         }
         false
     }
 
+    // Clippy takes the PlanetDetails props for no-op statements
+    #[allow(clippy::unnecessary_operation)]
     fn view(&self) -> Html {
-        if let Some(selected_star) = &self.props.universe.selected_star {
+        if let Some(selected_star) = &self.props.view.selected_star {
             let heading = format!("Planets/Resources in the {} System",
                                   selected_star.name);
             let loading = selected_star.name.eq(&"".to_string());
+            let favourite = self.props.favourites.get(&selected_star.nat_id);
+            let star_id = selected_star.nat_id.clone();
             html! {
                 <div hidden={loading}>
-                    <h3>{heading}</h3>
-                    { for self.props.universe.planets_for_selected_star()
+                    <div class="system-heading">
+                        <h3>{heading}</h3>
+                        <i
+                            class=classes!("fa-star", "favourite", favourite_class(favourite.is_some()))
+                            title="Favourite system"
+                            onclick=self.link.callback(move |_| {
+                                Msg::Favourite(FavouriteKind::System, star_id.clone())
+                            })
+                        />
+                    </div>
+                    {self.view_nearest_cx(selected_star)}
+                    { match favourite {
+                        Some(f) if !f.note.is_empty() => html! { <div class="note">{&f.note}</div> },
+                        _ => html! {},
+                    }}
+                    { for self.props.universe.planets_for_star(selected_star)
                         .iter().map(|p| {
                             let resources = self.props.universe.resources_for_planet(p);
                             html! {
@@ -65,7 +115,15 @@ impl Component for System {
                                     highlight_env=true
                                     env_filter={self.props.env_filter}
                                     universe={&self.props.universe}
+                                    view={&self.props.view}
+                                    extraction={self.props.extraction}
+                                    pinned={self.props.pinned.contains(&p.nat_id)}
+                                    favourite={self.props.favourites.get(&p.nat_id).cloned()}
                                     heading_click=self.link.callback(Msg::OnHeadingClick)
+                                    pin_click=self.link.callback(Msg::Pin)
+                                    favourite_click=self.link.callback(|nat_id| {
+                                        Msg::Favourite(FavouriteKind::Planet, nat_id)
+                                    })
                                 />
                             }
                         })
@@ -77,3 +135,8 @@ impl Component for System {
         }
     }
 }
+
+// Solid when a favourite, an outline to add one
+pub fn favourite_class(is_favourite: bool) -> &'static str {
+    if is_favourite { "fas" } else { "far" }
+}
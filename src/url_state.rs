@@ -0,0 +1,270 @@
+use wasm_bindgen::JsValue;
+
+use crate::models::*;
+use crate::query::{parse_unchecked, to_query};
+
+// Everything needed to reproduce what another user was looking at, kept
+// in the URL fragment as `key=value` pairs, eg.
+// #env=1&normal=1&surface=Rocky&grav=Low&temp=Normal&pres=Normal&res=FEO&star=AX-383
+#[derive(PartialEq, Clone, Debug)]
+pub struct UrlState {
+    pub filters: Filters,
+    pub selected_res: Option<String>,
+    pub selected_star: Option<String>,
+    pub scale: f64,
+    pub pan: (f64, f64),
+    pub show_cx: bool,
+    pub show_routes: bool,
+    pub normalisation: Normalisation,
+}
+
+impl UrlState {
+    pub fn new(filters: &Filters, view: &View, map_features: &MapFeatures) -> Self {
+        Self {
+            filters: filters.clone(),
+            selected_res: view.selected_res.clone(),
+            selected_star: view.selected_star.as_ref().map(|s| s.nat_id.clone()),
+            scale: map_features.selected_scale,
+            pan: scroll_position(),
+            show_cx: map_features.show_cx,
+            show_routes: map_features.show_routes,
+            normalisation: view.normalisation,
+        }
+    }
+    pub fn to_fragment(&self) -> String {
+        let f = &self.filters;
+        let mut pairs = vec![
+            format!("env={}", bool_param(f.env_filter)),
+            format!("normal={}", bool_param(f.inc_normal)),
+            format!("surface={}", f.surface),
+            format!("grav={}", f.gravity),
+            format!("temp={}", f.temp),
+            format!("pres={}", f.pressure),
+        ];
+        // Resource, jump and radius limits travel as query text
+        let limits = Filters {
+            env_filter: false,
+            res_filters: f.res_filters.clone(),
+            jump_filters: f.jump_filters.clone(),
+            radius_filters: f.radius_filters.clone(),
+            ..Filters::new()
+        };
+        let q = to_query(&limits, &None);
+        if !q.is_empty() {
+            pairs.push(format!("q={}", encode(&q)));
+        }
+        if let Some(res) = &self.selected_res {
+            pairs.push(format!("res={}", encode(res)));
+        }
+        if let Some(star) = &self.selected_star {
+            pairs.push(format!("star={}", encode(star)));
+        }
+        pairs.push(format!("scale={}", self.scale));
+        pairs.push(format!("pan={},{}", self.pan.0.round(), self.pan.1.round()));
+        pairs.push(format!("cx={}", bool_param(self.show_cx)));
+        pairs.push(format!("routes={}", bool_param(self.show_routes)));
+        if self.normalisation == Normalisation::Percentile {
+            pairs.push("norm=pctl".to_string());
+        }
+        pairs.join("&")
+    }
+    // Unknown keys and bad values are skipped so that older links keep
+    // working as the state grows.
+    pub fn from_fragment(fragment: &str) -> Option<Self> {
+        let fragment = fragment.trim_start_matches('#');
+        if fragment.is_empty() {
+            return None;
+        }
+        let map_features = MapFeatures::new();
+        let mut state = Self {
+            filters: Filters::new(),
+            selected_res: None,
+            selected_star: None,
+            scale: map_features.selected_scale,
+            pan: (0.0, 0.0),
+            show_cx: map_features.show_cx,
+            show_routes: map_features.show_routes,
+            normalisation: Normalisation::PercentOfMax,
+        };
+        for pair in fragment.split('&') {
+            let mut kv = pair.splitn(2, '=');
+            let key = kv.next().unwrap_or_default();
+            let value = kv.next().unwrap_or_default();
+            match key {
+                "env" => state.filters.env_filter = value == "1",
+                "normal" => state.filters.inc_normal = value == "1",
+                "surface" => state.filters.surface = to_surface_option(value),
+                "grav" => state.filters.gravity = to_env_option(value),
+                "temp" => state.filters.temp = to_env_option(value),
+                "pres" => state.filters.pressure = to_env_option(value),
+                "res" if !value.is_empty() => state.selected_res = Some(decode(value)),
+                "star" if !value.is_empty() => state.selected_star = Some(decode(value)),
+                "scale" => {
+                    if let Ok(scale) = value.parse() {
+                        state.scale = scale;
+                    }
+                }
+                "pan" => {
+                    let xy: Vec<f64> = value.split(',').filter_map(|v| v.parse().ok()).collect();
+                    if let [x, y] = xy[..] {
+                        state.pan = (x, y);
+                    }
+                }
+                "q" => {
+                    if let Ok(query) = parse_unchecked(&decode(value)) {
+                        state.filters.res_filters = query.filters.res_filters;
+                        state.filters.jump_filters = query.filters.jump_filters;
+                        state.filters.radius_filters = query.filters.radius_filters;
+                    }
+                }
+                "cx" => state.show_cx = value == "1",
+                "routes" => state.show_routes = value == "1",
+                "norm" if value == "pctl" => state.normalisation = Normalisation::Percentile,
+                _ => (),
+            }
+        }
+        Some(state)
+    }
+    pub fn from_location() -> Option<Self> {
+        let hash = web_sys::window()?.location().hash().ok()?;
+        Self::from_fragment(&hash)
+    }
+    // replace_state so that every filter tweak doesn't add a history entry
+    pub fn write_location(&self) {
+        if let Some(window) = web_sys::window() {
+            if let Ok(history) = window.history() {
+                let url = format!("#{}", self.to_fragment());
+                let _ = history.replace_state_with_url(&JsValue::NULL, "", Some(&url));
+            }
+        }
+    }
+}
+
+fn bool_param(b: bool) -> &'static str {
+    if b { "1" } else { "0" }
+}
+
+fn encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'(' | b')' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = s.get(i + 1..i + 3).and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(b)) => {
+                out.push(b);
+                i += 3;
+            }
+            (b, _) => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+pub fn scroll_position() -> (f64, f64) {
+    match web_sys::window() {
+        Some(w) => (w.scroll_x().unwrap_or(0.0), w.scroll_y().unwrap_or(0.0)),
+        None => (0.0, 0.0),
+    }
+}
+
+pub fn scroll_to(pan: (f64, f64)) {
+    if let Some(w) = web_sys::window() {
+        w.scroll_to_with_x_and_y(pan.0, pan.1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(filters: Filters) -> UrlState {
+        UrlState {
+            filters,
+            selected_res: None,
+            selected_star: None,
+            scale: 1.0,
+            pan: (0.0, 0.0),
+            show_cx: true,
+            show_routes: true,
+            normalisation: Normalisation::PercentOfMax,
+        }
+    }
+
+    fn round_trip(state: &UrlState) {
+        let fragment = state.to_fragment();
+        assert_eq!(
+            UrlState::from_fragment(&format!("#{}", fragment)).as_ref(),
+            Some(state),
+            "{}",
+            fragment
+        );
+    }
+
+    #[test]
+    fn every_filter_variant_round_trips() {
+        let surfaces = [SurfaceOption::Rocky, SurfaceOption::Gaseous, SurfaceOption::Both];
+        let envs = [
+            EnvironmentOption::Normal,
+            EnvironmentOption::Low,
+            EnvironmentOption::High,
+            EnvironmentOption::Ignore,
+            EnvironmentOption::Range(0.25, 1.8),
+            EnvironmentOption::Range(-40.0, 75.5),
+        ];
+        for surface in surfaces.iter() {
+            for env in envs.iter() {
+                for flag in [false, true].iter() {
+                    round_trip(&state(Filters {
+                        env_filter: *flag,
+                        inc_normal: !*flag,
+                        surface: surface.clone(),
+                        gravity: env.clone(),
+                        temp: env.clone(),
+                        pressure: env.clone(),
+                        ..Filters::new()
+                    }));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn limits_selection_and_view_round_trip() {
+        let q = parse_unchecked("FEO LST>40% O<=0.1 jumps(ANT)<=5 dist(\"Antares I\")>300").unwrap();
+        let mut s = state(q.filters);
+        s.selected_res = Some("H2O&O=2 %".to_string());
+        s.selected_star = Some("Antares #1".to_string());
+        s.scale = 2.5;
+        s.pan = (120.0, -35.0);
+        s.show_cx = false;
+        s.show_routes = false;
+        s.normalisation = Normalisation::Percentile;
+        round_trip(&s);
+        assert!(!s.to_fragment().contains(' '));
+    }
+
+    #[test]
+    fn bad_values_are_skipped() {
+        let s = UrlState::from_fragment("#grav=heavy&scale=big&pan=1&what=ever&res=").unwrap();
+        assert_eq!(s.filters.gravity, EnvironmentOption::Ignore);
+        assert_eq!(s.scale, MapFeatures::new().selected_scale);
+        assert_eq!(s.pan, (0.0, 0.0));
+        assert_eq!(s.selected_res, None);
+        assert_eq!(UrlState::from_fragment("#"), None);
+    }
+}
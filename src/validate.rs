@@ -0,0 +1,304 @@
+// Checks the data files against each other. The rest of the code assumes
+// every reference resolves, so dangling ones are errors; anything odd but
+// harmless is a warning.
+use serde::de::{self, Deserialize, Deserializer};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+
+use crate::models::{Planet, Resource, Star};
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum IssueKind {
+    DuplicateId,
+    UnknownSystem,
+    UnknownPlanet,
+    UnknownConnection,
+    OneWayConnection,
+    OutOfRange,
+    UnknownType,
+    UnknownField,
+}
+
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct Issue {
+    pub severity: Severity,
+    pub kind: IssueKind,
+    // stars, planets or resources
+    pub file: &'static str,
+    // The record at fault, eg. a nat_id
+    pub id: String,
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{}: {} {}: {}", severity, self.file, self.id, self.message)
+    }
+}
+
+// An Issue as it comes from the filter worker, the file not yet matched
+// back to a data file's name
+#[derive(Deserialize)]
+struct IssueData {
+    severity: Severity,
+    kind: IssueKind,
+    file: String,
+    id: String,
+    message: String,
+}
+
+impl<'de> Deserialize<'de> for Issue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = IssueData::deserialize(deserializer)?;
+        let file = ["stars", "planets", "resources"]
+            .iter()
+            .find(|f| **f == data.file)
+            .ok_or_else(|| de::Error::custom(format!("not a data file {}", data.file)))?;
+        Ok(Self {
+            severity: data.severity,
+            kind: data.kind,
+            file,
+            id: data.id,
+            message: data.message,
+        })
+    }
+}
+
+const RESOURCE_TYPES: [&str; 3] = ["MINERAL", "GASEOUS", "LIQUID"];
+
+pub fn validate(stars: &[Star], planets: &[Planet], resources: &[Resource]) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let mut issue = |severity, kind, file, id: &str, message: String| {
+        issues.push(Issue { severity, kind, file, id: id.to_string(), message });
+    };
+
+    let mut star_ids: HashMap<&str, &Star> = HashMap::new();
+    let mut star_nat_ids = HashSet::new();
+    for s in stars {
+        if star_ids.insert(s.sys_id.as_str(), s).is_some() {
+            issue(Severity::Error, IssueKind::DuplicateId, "stars", &s.nat_id,
+                format!("sys_id {} is used more than once", s.sys_id));
+        }
+        if !star_nat_ids.insert(s.nat_id.as_str()) {
+            issue(Severity::Warning, IssueKind::DuplicateId, "stars", &s.nat_id,
+                "nat_id is used more than once".to_string());
+        }
+        for v in [s.x, s.y, s.z] {
+            if !v.is_finite() {
+                issue(Severity::Error, IssueKind::OutOfRange, "stars", &s.nat_id,
+                    format!("coordinate {} is not a number", v));
+            }
+        }
+    }
+    for s in stars {
+        for c in &s.connections {
+            match star_ids.get(c.connection.as_str()) {
+                None => issue(Severity::Error, IssueKind::UnknownConnection, "stars", &s.nat_id,
+                    format!("connection to unknown system {}", c.connection)),
+                Some(other) if !other.connections.iter().any(|b| b.connection == s.sys_id) => {
+                    issue(Severity::Warning, IssueKind::OneWayConnection, "stars", &s.nat_id,
+                        format!("connects to {} but not back", other.nat_id))
+                }
+                _ => (),
+            }
+        }
+    }
+
+    let mut planet_ids = HashSet::new();
+    for p in planets {
+        if !planet_ids.insert(p.nat_id.as_str()) {
+            issue(Severity::Error, IssueKind::DuplicateId, "planets", &p.nat_id,
+                "nat_id is used more than once".to_string());
+        }
+        if !star_ids.contains_key(p.sys_id.as_str()) {
+            issue(Severity::Error, IssueKind::UnknownSystem, "planets", &p.nat_id,
+                format!("unknown system {}", p.sys_id));
+        }
+        let ranges = [
+            ("gravity", p.gravity, 0.0, f64::MAX),
+            ("pressure", p.pressure, 0.0, f64::MAX),
+            ("temp", p.temp, -273.15, f64::MAX),
+            ("fertility", p.fertility, -1.0, 1.0),
+        ];
+        for (name, v, min, max) in ranges.iter() {
+            if !v.is_finite() || v < min || v > max {
+                issue(Severity::Warning, IssueKind::OutOfRange, "planets", &p.nat_id,
+                    format!("{} {} is out of range", name, v));
+            }
+        }
+    }
+
+    let mut planet_tickers = HashSet::new();
+    for r in resources {
+        let id = format!("{} {}", r.planet, r.ticker);
+        if !planet_ids.contains(r.planet.as_str()) {
+            issue(Severity::Error, IssueKind::UnknownPlanet, "resources", &id,
+                format!("unknown planet {}", r.planet));
+        }
+        if !planet_tickers.insert((r.planet.as_str(), r.ticker.as_str())) {
+            issue(Severity::Warning, IssueKind::DuplicateId, "resources", &id,
+                "ticker is listed more than once for the planet".to_string());
+        }
+        if !RESOURCE_TYPES.contains(&r.typ.to_ascii_uppercase().as_str()) {
+            issue(Severity::Warning, IssueKind::UnknownType, "resources", &id,
+                format!("unknown type {}", r.typ));
+        }
+        if !r.factor.is_finite() || r.factor <= 0.0 || r.factor > 1.0 {
+            issue(Severity::Warning, IssueKind::OutOfRange, "resources", &id,
+                format!("factor {} is out of range", r.factor));
+        }
+    }
+
+    // Fields the app doesn't know were skipped when loading, once per field
+    let mut unknown: BTreeMap<(&'static str, &str), usize> = BTreeMap::new();
+    let names = stars.iter().flat_map(|s| s.unknown.names().map(|n| ("stars", n.as_str())))
+        .chain(planets.iter().flat_map(|p| p.unknown.names().map(|n| ("planets", n.as_str()))))
+        .chain(resources.iter().flat_map(|r| r.unknown.names().map(|n| ("resources", n.as_str()))));
+    for name in names {
+        *unknown.entry(name).or_default() += 1;
+    }
+    for ((file, field), count) in unknown {
+        issue(Severity::Warning, IssueKind::UnknownField, file, field,
+            format!("unknown field ignored on {} records", count));
+    }
+    issues
+}
+
+pub fn has_errors(issues: &[Issue]) -> bool {
+    issues.iter().any(|i| i.severity == Severity::Error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Connection;
+
+    fn star(sys_id: &str, nat_id: &str, connections: &[&str]) -> Star {
+        Star {
+            sys_id: sys_id.to_string(),
+            name: nat_id.to_string(),
+            nat_id: nat_id.to_string(),
+            connections: connections.iter().map(|c| Connection { connection: c.to_string() }).collect(),
+            ..Star::new()
+        }
+    }
+
+    fn planet(sys_id: &str, nat_id: &str) -> Planet {
+        Planet {
+            sys_id: sys_id.to_string(),
+            name: nat_id.to_string(),
+            nat_id: nat_id.to_string(),
+            surface: true,
+            gravity: 1.0,
+            temp: 20.0,
+            pressure: 1.0,
+            fertility: 0.0,
+            orbit_index: None,
+            infrastructure: Vec::new(),
+            unknown: Default::default(),
+        }
+    }
+
+    fn resource(planet: &str, ticker: &str, factor: f64) -> Resource {
+        Resource {
+            planet: planet.to_string(),
+            ticker: ticker.to_string(),
+            typ: "MINERAL".to_string(),
+            factor,
+            unknown: Default::default(),
+        }
+    }
+
+    fn stars() -> Vec<Star> {
+        vec![star("s1", "AA-001", &["s2"]), star("s2", "BB-002", &["s1"])]
+    }
+
+    // (severity, kind, id) of each issue, in the order found
+    fn found(stars: &[Star], planets: &[Planet], resources: &[Resource]) -> Vec<(Severity, IssueKind, String)> {
+        validate(stars, planets, resources)
+            .into_iter()
+            .map(|i| (i.severity, i.kind, i.id))
+            .collect()
+    }
+
+    #[test]
+    fn clean_data_has_no_issues() {
+        let planets = vec![planet("s1", "AA-001a")];
+        let resources = vec![resource("AA-001a", "FEO", 0.3)];
+        assert!(validate(&stars(), &planets, &resources).is_empty());
+    }
+
+    #[test]
+    fn dangling_references_are_errors() {
+        let planets = vec![planet("s9", "ZZ-009a")];
+        let resources = vec![resource("ZZ-009b", "FEO", 0.3)];
+        assert_eq!(found(&stars(), &planets, &resources), [
+            (Severity::Error, IssueKind::UnknownSystem, "ZZ-009a".to_string()),
+            (Severity::Error, IssueKind::UnknownPlanet, "ZZ-009b FEO".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn connections_unknown_and_one_way() {
+        let stars = vec![
+            star("s1", "AA-001", &["s9", "s2"]),
+            star("s2", "BB-002", &[]),
+        ];
+        let issues = validate(&stars, &[], &[]);
+        assert_eq!(issues.len(), 2);
+        assert_eq!((issues[0].severity, issues[0].kind), (Severity::Error, IssueKind::UnknownConnection));
+        assert_eq!(issues[0].message, "connection to unknown system s9");
+        assert_eq!((issues[1].severity, issues[1].kind), (Severity::Warning, IssueKind::OneWayConnection));
+        assert_eq!(issues[1].message, "connects to BB-002 but not back");
+        assert!(has_errors(&issues));
+    }
+
+    #[test]
+    fn duplicate_ids() {
+        let mut stars = stars();
+        stars.push(star("s1", "CC-003", &["s2"]));
+        stars.push(star("s3", "AA-001", &[]));
+        let planets = vec![planet("s1", "AA-001a"), planet("s1", "AA-001a")];
+        let resources = vec![resource("AA-001a", "FEO", 0.3), resource("AA-001a", "FEO", 0.2)];
+        assert_eq!(found(&stars, &planets, &resources), [
+            (Severity::Error, IssueKind::DuplicateId, "CC-003".to_string()),
+            (Severity::Warning, IssueKind::DuplicateId, "AA-001".to_string()),
+            (Severity::Error, IssueKind::DuplicateId, "AA-001a".to_string()),
+            (Severity::Warning, IssueKind::DuplicateId, "AA-001a FEO".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn values_out_of_range() {
+        let mut stars = stars();
+        stars[0].x = f64::NAN;
+        let mut cold = planet("s1", "AA-001a");
+        cold.temp = -300.0;
+        cold.fertility = 1.5;
+        let mut odd = resource("AA-001a", "FEO", f64::INFINITY);
+        odd.typ = "PLASMA".to_string();
+        let issues = validate(&stars, &[cold], &[odd, resource("AA-001a", "H2O", 0.0)]);
+        let messages: Vec<(Severity, &str)> = issues.iter().map(|i| (i.severity, i.message.as_str())).collect();
+        assert_eq!(messages, [
+            (Severity::Error, "coordinate NaN is not a number"),
+            (Severity::Warning, "temp -300 is out of range"),
+            (Severity::Warning, "fertility 1.5 is out of range"),
+            (Severity::Warning, "unknown type PLASMA"),
+            (Severity::Warning, "factor inf is out of range"),
+            (Severity::Warning, "factor 0 is out of range"),
+        ]);
+    }
+}
@@ -0,0 +1,25 @@
+// Keeps the app shell (page, wasm, js, css) for offline use. Network first,
+// so a new build is picked up as soon as it's served; the data files are
+// cached by the app itself (see src/data_cache.rs).
+const SHELL_CACHE = 'prun-shell';
+
+self.addEventListener('install', () => self.skipWaiting());
+self.addEventListener('activate', event => event.waitUntil(self.clients.claim()));
+
+self.addEventListener('fetch', event => {
+  const request = event.request;
+  if (request.method !== 'GET' || new URL(request.url).origin !== self.location.origin) {
+    return;
+  }
+  event.respondWith(
+    fetch(request)
+      .then(response => {
+        if (response.ok) {
+          const copy = response.clone();
+          caches.open(SHELL_CACHE).then(cache => cache.put(request, copy));
+        }
+        return response;
+      })
+      .catch(() => caches.match(request))
+  );
+});